@@ -10,7 +10,7 @@ use std::{
     any::type_name,
     cmp::min,
     collections::hash_map::RandomState,
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     default::Default,
     fs,
     hash::{BuildHasher, Hasher},
@@ -19,7 +19,8 @@ use std::{
 };
 use walkdir::WalkDir;
 use xxhrs::{
-    EntropyPool, RandomStateXXH32, RandomStateXXH3_128, RandomStateXXH3_64, RandomStateXXH64,
+    active_backend, set_backend, Backend, EntropyPool, RandomStateXXH32, RandomStateXXH3_128,
+    RandomStateXXH3_128Seeded, RandomStateXXH3_64, RandomStateXXH3_64Seeded, RandomStateXXH64,
     XXH32, XXH3_128, XXH3_64, XXH64,
 };
 
@@ -139,6 +140,11 @@ fn bench_hash(c: &mut Criterion) {
 
         b_buildhash!("xxhrs::RandomStateXXH3_64", RandomStateXXH3_64);
         b_buildhash!("xxhrs::RandomStateXXH3_128", RandomStateXXH3_128);
+        b_buildhash!("xxhrs::RandomStateXXH3_64Seeded", RandomStateXXH3_64Seeded);
+        b_buildhash!(
+            "xxhrs::RandomStateXXH3_128Seeded",
+            RandomStateXXH3_128Seeded
+        );
 
         b_buildhash!("RandomState", RandomState);
         b_buildhash!(
@@ -163,7 +169,91 @@ fn bench_hash(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, bench_entropy_derivation, bench_hash);
+fn bench_small_keys(c: &mut Criterion) {
+    let mut g = c.benchmark_group("small_keys");
+    g.sample_size(1000);
+
+    const KEYS: u64 = 1000;
+
+    // Lookups in a hash map with small keys; this is dominated by
+    // the cost of constructing the hasher.
+    macro_rules! b_map {
+        ($name:expr, $t:ty) => {{
+            let mut m = HashMap::<u64, u64, $t>::default();
+            for k in 0..KEYS {
+                m.insert(k, k);
+            }
+            g.bench_function(format!("{}/u64", $name), |b| {
+                b.iter(|| (0..KEYS).filter_map(|k| m.get(black_box(&k))).sum::<u64>());
+            });
+
+            let mut m = HashMap::<String, u64, $t>::default();
+            for k in 0..KEYS {
+                m.insert(format!("key{}", k), k);
+            }
+            let keys = (0..KEYS).map(|k| format!("key{}", k)).collect::<Vec<_>>();
+            g.bench_function(format!("{}/str", $name), |b| {
+                b.iter(|| keys.iter().filter_map(|k| m.get(black_box(k))).sum::<u64>());
+            });
+        }};
+    }
+
+    b_map!("xxhrs::RandomStateXXH3_64", RandomStateXXH3_64);
+    b_map!("xxhrs::RandomStateXXH3_64Seeded", RandomStateXXH3_64Seeded);
+    b_map!("xxhrs::RandomStateXXH64", RandomStateXXH64);
+    b_map!("RandomState", RandomState);
+}
+
+fn bench_backends(c: &mut Criterion) {
+    let mut g = c.benchmark_group("backends");
+    g.sample_size(1000);
+
+    // Throughput of the XXH3 kernels on long inputs; kernels not
+    // supported by this cpu are skipped
+    let default = active_backend();
+    for backend in [
+        Backend::Scalar,
+        Backend::Sse2,
+        Backend::Avx2,
+        Backend::Avx512,
+    ]
+    .iter()
+    .copied()
+    {
+        if set_backend(backend).is_err() {
+            continue;
+        }
+
+        for sz in [1024, DATA.len()].iter().copied() {
+            macro_rules! b {
+                ($name:expr, $fn:expr) => {{
+                    let id = BenchmarkId::new(format!("{}/{:?}", $name, backend), sz);
+                    g.throughput(Throughput::Bytes(sz as u64));
+                    g.bench_with_input(id, &DATA[0..sz], |b, d| {
+                        b.iter(|| $fn(d));
+                    });
+                }};
+            }
+
+            b!("XXH3_64::hash", XXH3_64::hash);
+            b!("XXH3_128::hash", XXH3_128::hash);
+            b!("XXH3_64::write", |d| {
+                let mut h = XXH3_64::new();
+                h.write(d);
+                h.finish()
+            });
+        }
+    }
+    set_backend(default).unwrap();
+}
+
+criterion_group!(
+    benches,
+    bench_entropy_derivation,
+    bench_hash,
+    bench_small_keys,
+    bench_backends
+);
 
 // Benchmark output
 
@@ -1,12 +1,78 @@
 extern crate bindgen;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use std::env;
 use std::path::PathBuf;
 
-fn try_main() -> Result<()> {
-    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+/// Read an XXHRS_* environment variable configuring the C build
+fn config_var(name: &str) -> Option<String> {
+    println!("cargo:rerun-if-env-changed={}", name);
+    env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+/// Parse a numeric XXHRS_* option, allowing values up to `max`
+fn config_num(name: &str, max: u8) -> Result<Option<u8>> {
+    match config_var(name) {
+        None => Ok(None),
+        Some(v) => match v.parse::<u8>() {
+            Ok(n) if n <= max => Ok(Some(n)),
+            _ => bail!(
+                "{} must be a number between 0 and {}, not {:?}",
+                name,
+                max,
+                v
+            ),
+        },
+    }
+}
+
+/// Preprocessor definitions and compiler flags
+type CConfig = (Vec<(&'static str, String)>, Vec<&'static str>);
 
+/// Macros & compiler flags for the xxhash compile options selected through
+/// XXHRS_* environment variables
+fn xxhash_config() -> Result<CConfig> {
+    let mut defines = Vec::new();
+    let mut flags = Vec::new();
+
+    // XXH_VECTOR; setting it disables the runtime dispatch on x86
+    if let Some(v) = config_var("XXHRS_VECTOR") {
+        let (vector, flag) = match v.to_lowercase().as_str() {
+            "0" | "scalar" => (0, None),
+            "1" | "sse2" => (1, Some("-msse2")),
+            "2" | "avx2" => (2, Some("-mavx2")),
+            "3" | "avx512" => (3, Some("-mavx512f")),
+            "4" | "neon" => (4, None),
+            "5" | "vsx" => (5, None),
+            "6" | "sve" => (6, None),
+            _ => bail!(
+                "XXHRS_VECTOR must be scalar, sse2, avx2, avx512, neon, vsx or sve, not {:?}",
+                v
+            ),
+        };
+        defines.push(("XXH_VECTOR", vector.to_string()));
+        flags.extend(flag);
+    }
+    if let Some(n) = config_num("XXHRS_SIZE_OPT", 2)? {
+        defines.push(("XXH_SIZE_OPT", n.to_string()));
+    }
+    if let Some(n) = config_num("XXHRS_FORCE_MEMORY_ACCESS", 3)? {
+        defines.push(("XXH_FORCE_MEMORY_ACCESS", n.to_string()));
+    }
+
+    // These remove the functions all of our hashers are built on
+    for name in &["XXHRS_NO_XXH3", "XXHRS_NO_LONG_LONG"] {
+        if config_var(name).is_some() {
+            bail!("{} is not supported: xxhrs requires XXH64 and XXH3", name);
+        }
+    }
+
+    Ok((defines, flags))
+}
+
+/// Compile the vendored xxhash
+#[cfg(not(feature = "system-xxhash"))]
+fn build_vendored() -> Result<bindgen::Builder> {
     // Configure C build
     env::set_var(
         "CFLAGS",
@@ -17,16 +83,139 @@ fn try_main() -> Result<()> {
         ),
     );
 
+    // Without std, build xxhash freestanding: XXH_NO_STDLIB drops malloc/free
+    // and our string.h maps memcpy & co to compiler builtins
+    let freestanding = env::var_os("CARGO_FEATURE_STD").is_none();
+
+    let (defines, flags) = xxhash_config()?;
+
     // Compile xxhash
-    cc::Build::new()
-        .file("src/xxhash_bindings.c")
-        .compile("xxhash");
+    let mut build = cc::Build::new();
+    build.file("src/xxhash_bindings.c");
+    for (name, value) in &defines {
+        build.define(name, value.as_str());
+    }
+    for flag in &flags {
+        build.flag_if_supported(flag);
+    }
+    if freestanding {
+        build
+            .include("src/freestanding")
+            .define("XXH_NO_STDLIB", None)
+            .flag_if_supported("-ffreestanding");
+    }
+    build.compile("xxhash");
+
+    println!("cargo:rerun-if-changed=src/freestanding/string.h");
+    let mut bindings = bindgen::Builder::default();
+    if freestanding {
+        bindings =
+            bindings.clang_args(&["-I./src/freestanding/", "-DXXH_NO_STDLIB", "-ffreestanding"]);
+    }
+    for (name, value) in &defines {
+        bindings = bindings.clang_arg(format!("-D{}={}", name, value));
+    }
+    Ok(bindings.clang_arg("-I./vendor/xxhash/"))
+}
+
+/// Gives the XXH* functions of the system libxxhash the namespaced names
+/// the bindings to the vendored copy have, so both can be used the same
+#[cfg(feature = "system-xxhash")]
+#[derive(Debug)]
+struct SystemNames {
+    /// Prepended to C symbols by the target's ABI
+    symbol_prefix: &'static str,
+}
+
+#[cfg(feature = "system-xxhash")]
+impl SystemNames {
+    const NAMESPACE: &'static str = "xxhrs_equodaeyiejoopibaeva_";
+
+    /// Our own XXHRS_* helpers are namespaced in the header already
+    fn is_xxhash_fn(name: &str) -> bool {
+        name.starts_with("XXH") && !name.starts_with("XXHRS_")
+    }
+}
+
+#[cfg(feature = "system-xxhash")]
+impl bindgen::callbacks::ParseCallbacks for SystemNames {
+    fn generated_name_override(&self, item: bindgen::callbacks::ItemInfo<'_>) -> Option<String> {
+        Some(item.name)
+            .filter(|name| Self::is_xxhash_fn(name))
+            .map(|name| format!("{}{}", Self::NAMESPACE, name))
+    }
+
+    fn generated_link_name_override(
+        &self,
+        item: bindgen::callbacks::ItemInfo<'_>,
+    ) -> Option<String> {
+        item.name
+            .strip_prefix(Self::NAMESPACE)
+            .filter(|name| Self::is_xxhash_fn(name))
+            .map(|name| format!("{}{}", self.symbol_prefix, name))
+    }
+}
+
+/// Link against the system libxxhash, compiling only our helpers
+#[cfg(feature = "system-xxhash")]
+fn build_system() -> Result<bindgen::Builder> {
+    use anyhow::Context;
+
+    let (defines, _) = xxhash_config()?;
+    if !defines.is_empty() {
+        println!(
+            "cargo:warning=XXHRS_* build options have no effect with the system-xxhash feature"
+        );
+    }
+
+    let lib = pkg_config::Config::new()
+        .atleast_version("0.8.2")
+        .probe("libxxhash")
+        .context("the system-xxhash feature requires libxxhash 0.8.2 or newer")?;
+
+    let mut build = cc::Build::new();
+    build
+        .file("src/xxhash_system.c")
+        .define("XXHRS_SYSTEM_XXHASH", None)
+        .includes(&lib.include_paths);
+    build.compile("xxhrs_system");
+
+    println!("cargo:rerun-if-changed=src/xxhash_system.c");
+    let apple = env::var("CARGO_CFG_TARGET_VENDOR")? == "apple";
+    let win32 = env::var("CARGO_CFG_TARGET_OS")? == "windows"
+        && env::var("CARGO_CFG_TARGET_ARCH")? == "x86";
+    let names = SystemNames {
+        symbol_prefix: if apple || win32 { "_" } else { "" },
+    };
+    Ok(bindgen::Builder::default()
+        .clang_arg("-DXXHRS_SYSTEM_XXHASH")
+        .clang_args(
+            lib.include_paths
+                .iter()
+                .map(|p| format!("-I{}", p.display())),
+        )
+        .parse_callbacks(Box::new(names)))
+}
+
+fn try_main() -> Result<()> {
+    // The native implementation needs neither the C library nor bindings
+    if env::var_os("CARGO_FEATURE_PURE_RUST").is_some() {
+        return Ok(());
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+
+    #[cfg(feature = "system-xxhash")]
+    let bindings = build_system()?;
+    #[cfg(not(feature = "system-xxhash"))]
+    let bindings = build_vendored()?;
 
     // Generate rust bindings
     println!("cargo:rerun-if-changed=src/bindings.h");
-    bindgen::Builder::default()
-        .clang_arg("-I./vendor/xxhash/")
+    bindings
         .header("src/xxhash_bindings.h")
+        .use_core()
+        .ctypes_prefix("::core::ffi")
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -40,6 +229,7 @@ fn try_main() -> Result<()> {
 
 fn main() {
     if let Err(er) = try_main() {
-        println!("{}", er);
+        eprintln!("{}", er);
+        std::process::exit(1);
     }
 }
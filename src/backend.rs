@@ -0,0 +1,70 @@
+use crate::{error::Error, C};
+use core::ffi::c_int;
+
+/// The kernels XXH3 can use to hash long inputs
+///
+/// On x86 and x86_64 the scalar, SSE2, AVX2 and AVX512 kernels are all
+/// compiled in and the best one supported by the cpu is chosen at runtime.
+/// On other platforms, the kernel is chosen at compile time. All kernels
+/// produce the same hashes.
+///
+/// The kernel used by the system libxxhash (`system-xxhash` feature) is
+/// `Unknown` and can not be changed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    Scalar,
+    Sse2,
+    Avx2,
+    Avx512,
+    Neon,
+    Vsx,
+    Sve,
+    Unknown,
+}
+
+impl Backend {
+    /// The XXH_VECTOR value identifying this kernel in xxhash
+    fn to_raw(self) -> c_int {
+        match self {
+            Backend::Scalar => 0,
+            Backend::Sse2 => 1,
+            Backend::Avx2 => 2,
+            Backend::Avx512 => 3,
+            Backend::Neon => 4,
+            Backend::Vsx => 5,
+            Backend::Sve => 6,
+            Backend::Unknown => -1,
+        }
+    }
+
+    pub(crate) fn from_raw(v: c_int) -> Backend {
+        match v {
+            1 => Backend::Sse2,
+            2 => Backend::Avx2,
+            3 => Backend::Avx512,
+            4 => Backend::Neon,
+            5 => Backend::Vsx,
+            6 => Backend::Sve,
+            0 => Backend::Scalar,
+            _ => Backend::Unknown,
+        }
+    }
+}
+
+/// The kernel currently used for XXH3 hashing
+pub fn active_backend() -> Backend {
+    Backend::from_raw(unsafe { C::XXHRS_backend() })
+}
+
+/// Use the given kernel for all XXH3 hashing in this process.
+///
+/// This is mostly useful for benchmarking and testing. Fails with
+/// `Error::UnsupportedBackend` if the kernel is not compiled in or not
+/// supported by the cpu.
+pub fn set_backend(backend: Backend) -> Result<(), Error> {
+    match unsafe { C::XXHRS_setBackend(backend.to_raw()) } {
+        0 => Ok(()),
+        _ => Err(Error::UnsupportedBackend(backend)),
+    }
+}
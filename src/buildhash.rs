@@ -1,12 +1,13 @@
 use crate::{
     entropy::EntropyPool,
+    error::Error,
+    masterpool::{derive_pool, derive_seed},
     xxh3::{XXH3_128, XXH3_64},
     xxhash::{XXH32, XXH64},
 };
-use getrandom::getrandom;
-use std::{default::Default, hash::BuildHasher};
+use std::{default::Default, fmt, hash::BuildHasher};
 
-/// xxhash 32 bit version. Generates a randomized seed using getrandom().
+/// xxhash 32 bit version. Derives a randomized seed from the master pool.
 /// Because the associated hasher generates u32, this does not implement BuildHash,
 /// however this features the same interface.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -22,13 +23,18 @@ impl Default for RandomStateXXH32 {
 }
 
 impl RandomStateXXH32 {
+    /// Panics if no random entropy is available; use try_new()
+    /// to handle that case.
     #[inline]
     pub fn new() -> Self {
-        let mut seed = [0u8; 4];
-        getrandom(&mut seed).unwrap();
-        Self {
-            seed: u32::from_ne_bytes(seed),
-        }
+        Self::try_new().unwrap()
+    }
+
+    #[inline]
+    pub fn try_new() -> Result<Self, Error> {
+        Ok(Self {
+            seed: derive_seed()? as u32,
+        })
     }
 
     #[inline]
@@ -37,7 +43,11 @@ impl RandomStateXXH32 {
     }
 }
 
-/// xxhash 64 bit version. Generates a randomized seed using getrandom().
+/// xxhash 64 bit version. Derives a randomized seed from the master pool.
+///
+/// All RandomState* instances are derived from a single random master pool
+/// that is drawn using getrandom() once per process (and again in the child
+/// after fork()), so creating them does not incur a syscall each time.
 /// Implements BuildHasher.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct RandomStateXXH64 {
@@ -52,12 +62,18 @@ impl Default for RandomStateXXH64 {
 }
 
 impl RandomStateXXH64 {
+    /// Panics if no random entropy is available; use try_new()
+    /// to handle that case.
+    #[inline]
     pub fn new() -> Self {
-        let mut seed = [0u8; 8];
-        getrandom(&mut seed).unwrap();
-        Self {
-            seed: u64::from_ne_bytes(seed),
-        }
+        Self::try_new().unwrap()
+    }
+
+    #[inline]
+    pub fn try_new() -> Result<Self, Error> {
+        Ok(Self {
+            seed: derive_seed()?,
+        })
     }
 }
 
@@ -70,7 +86,7 @@ impl BuildHasher for RandomStateXXH64 {
     }
 }
 
-/// xxh3 64 bit version. Generates a randomized seed using getrandom().
+/// xxh3 64 bit version. Derives a randomized entropy pool from the master pool.
 /// Implements BuildHasher.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct RandomStateXXH3_64 {
@@ -85,11 +101,18 @@ impl Default for RandomStateXXH3_64 {
 }
 
 impl RandomStateXXH3_64 {
+    /// Panics if no random entropy is available; use try_new()
+    /// to handle that case.
     #[inline]
     pub fn new() -> Self {
-        Self {
-            pool: EntropyPool::randomize(),
-        }
+        Self::try_new().unwrap()
+    }
+
+    #[inline]
+    pub fn try_new() -> Result<Self, Error> {
+        Ok(Self {
+            pool: derive_pool()?,
+        })
     }
 }
 
@@ -102,7 +125,7 @@ impl BuildHasher for RandomStateXXH3_64 {
     }
 }
 
-/// xxh3 128 bit version. Generates a randomized seed using getrandom().
+/// xxh3 128 bit version. Derives a randomized entropy pool from the master pool.
 /// Because the associated hasher generates u32, this does not implement BuildHash,
 /// however this features the same interface.
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -118,15 +141,168 @@ impl Default for RandomStateXXH3_128 {
 }
 
 impl RandomStateXXH3_128 {
+    /// Panics if no random entropy is available; use try_new()
+    /// to handle that case.
+    #[inline]
+    pub fn new() -> Self {
+        Self::try_new().unwrap()
+    }
+
+    #[inline]
+    pub fn try_new() -> Result<Self, Error> {
+        Ok(Self {
+            pool: derive_pool()?,
+        })
+    }
+
+    #[inline]
+    pub fn build_hasher(&self) -> XXH3_128<'static> {
+        XXH3_128::with_entropy(&self.pool)
+    }
+}
+
+/// xxh3 64 bit version using a seed instead of an entropy pool. Derives
+/// a randomized seed from the master pool.
+///
+/// The hasher for the seed is prepared once and copied by build_hasher(),
+/// so building a hasher does not derive a secret from the seed again.
+/// This makes building hashers at least as cheap as with
+/// RandomStateXXH3_64 (see the small_keys benchmark); cloning this copies
+/// the prepared hasher.
+/// Implements BuildHasher.
+#[derive(Clone)]
+pub struct RandomStateXXH3_64Seeded {
+    seed: u64,
+    hasher: XXH3_64<'static>,
+}
+
+impl Default for RandomStateXXH3_64Seeded {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandomStateXXH3_64Seeded {
+    /// Panics if no random entropy is available; use try_new()
+    /// to handle that case.
+    #[inline]
+    pub fn new() -> Self {
+        Self::try_new().unwrap()
+    }
+
+    #[inline]
+    pub fn try_new() -> Result<Self, Error> {
+        Ok(Self::with_seed(derive_seed()?))
+    }
+
+    /// Uses the given seed instead of a randomized one.
+    #[inline]
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            hasher: XXH3_64::with_seed(seed),
+        }
+    }
+
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for RandomStateXXH3_64Seeded {
+    type Hasher = XXH3_64<'static>;
+
+    #[inline]
+    fn build_hasher(&self) -> Self::Hasher {
+        self.hasher.clone()
+    }
+}
+
+impl PartialEq for RandomStateXXH3_64Seeded {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+}
+
+impl Eq for RandomStateXXH3_64Seeded {}
+
+impl fmt::Debug for RandomStateXXH3_64Seeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RandomStateXXH3_64Seeded")
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+/// xxh3 128 bit version using a seed instead of an entropy pool. Derives
+/// a randomized seed from the master pool.
+///
+/// Like RandomStateXXH3_64Seeded, this prepares the hasher for the seed
+/// once and copies it in build_hasher().
+/// Because the associated hasher generates u128, this does not implement BuildHash,
+/// however this features the same interface.
+#[derive(Clone)]
+pub struct RandomStateXXH3_128Seeded {
+    seed: u64,
+    hasher: XXH3_128<'static>,
+}
+
+impl Default for RandomStateXXH3_128Seeded {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandomStateXXH3_128Seeded {
+    /// Panics if no random entropy is available; use try_new()
+    /// to handle that case.
     #[inline]
     pub fn new() -> Self {
+        Self::try_new().unwrap()
+    }
+
+    #[inline]
+    pub fn try_new() -> Result<Self, Error> {
+        Ok(Self::with_seed(derive_seed()?))
+    }
+
+    /// Uses the given seed instead of a randomized one.
+    #[inline]
+    pub fn with_seed(seed: u64) -> Self {
         Self {
-            pool: EntropyPool::randomize(),
+            seed,
+            hasher: XXH3_128::with_seed(seed),
         }
     }
 
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     #[inline]
     pub fn build_hasher(&self) -> XXH3_128<'static> {
-        XXH3_128::with_entropy(&self.pool)
+        self.hasher.clone()
+    }
+}
+
+impl PartialEq for RandomStateXXH3_128Seeded {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+}
+
+impl Eq for RandomStateXXH3_128Seeded {}
+
+impl fmt::Debug for RandomStateXXH3_128Seeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RandomStateXXH3_128Seeded")
+            .field("seed", &self.seed)
+            .finish()
     }
 }
@@ -0,0 +1,57 @@
+use crate::{backend::Backend, C};
+use core::convert::TryFrom;
+
+/// The configuration xxhash was built with
+///
+/// The XXH3 kernel and the upstream tuning macros can be set at build
+/// time through the XXHRS_VECTOR, XXHRS_SIZE_OPT and
+/// XXHRS_FORCE_MEMORY_ACCESS environment variables.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct BuildInfo {
+    /// The native rust implementation is used instead of the C library
+    pub pure_rust: bool,
+    /// The system libxxhash is used instead of the vendored copy
+    pub system_xxhash: bool,
+    /// The C library was compiled without libc (std feature disabled)
+    pub freestanding: bool,
+    /// The XXH3 kernel selected at compile time (XXH_VECTOR); with
+    /// runtime dispatch, the one used until dispatch is initialized.
+    /// `Unknown` for the system libxxhash.
+    pub vector: Backend,
+    /// The XXH3 kernel is chosen at runtime; see `active_backend()`
+    pub runtime_dispatch: bool,
+    /// XXH_SIZE_OPT; higher values trade speed for code size. `None`
+    /// unless the vendored copy is compiled.
+    pub size_opt: Option<u8>,
+    /// XXH_FORCE_MEMORY_ACCESS; the method used for unaligned reads.
+    /// `None` unless the vendored copy is compiled.
+    pub force_memory_access: Option<u8>,
+}
+
+/// Report the configuration xxhash was built with
+pub fn build_info() -> BuildInfo {
+    let config = unsafe { C::XXHRS_config() };
+    BuildInfo {
+        pure_rust: cfg!(feature = "pure-rust"),
+        system_xxhash: cfg!(all(feature = "system-xxhash", not(feature = "pure-rust"))),
+        freestanding: cfg!(not(any(
+            feature = "pure-rust",
+            feature = "system-xxhash",
+            feature = "std"
+        ))),
+        vector: Backend::from_raw(config.vector),
+        runtime_dispatch: config.dispatch != 0,
+        size_opt: u8::try_from(config.sizeOpt).ok(),
+        force_memory_access: u8::try_from(config.forceMemoryAccess).ok(),
+    }
+}
+
+/// The version of the xxhash library in use, as
+/// `major * 100 * 100 + minor * 100 + release`; 802 for v0.8.2.
+///
+/// With the `pure-rust` feature, this is the version whose output the
+/// native implementation reproduces.
+pub fn xxhash_version() -> u32 {
+    unsafe { C::XXH_versionNumber() }
+}
@@ -0,0 +1,839 @@
+// const fn implementations of XXH32, XXH64 and XXH3, so hashes of constant
+// data can be computed at compile time.
+//
+// const fn can not use iterators, range indexing or mutable references, so
+// this is a port of xxhash.h working with offsets into the input and
+// passing state by value. The results are identical to the C
+// implementation.
+//
+// This is the only port of the algorithms in the crate: the hashers use
+// the XXH3 short input paths at runtime, since the overhead of calling
+// into the C library is significant for short inputs, and native.rs
+// builds the pure rust version of the C API from the functions here.
+
+pub(crate) const PRIME32_1: u32 = 0x9E3779B1;
+pub(crate) const PRIME32_2: u32 = 0x85EBCA77;
+pub(crate) const PRIME32_3: u32 = 0xC2B2AE3D;
+pub(crate) const PRIME32_4: u32 = 0x27D4EB2F;
+pub(crate) const PRIME32_5: u32 = 0x165667B1;
+pub(crate) const PRIME64_1: u64 = 0x9E3779B185EBCA87;
+pub(crate) const PRIME64_2: u64 = 0xC2B2AE3D27D4EB4F;
+pub(crate) const PRIME64_3: u64 = 0x165667B19E3779F9;
+pub(crate) const PRIME64_4: u64 = 0x85EBCA77C2B2AE63;
+pub(crate) const PRIME64_5: u64 = 0x27D4EB2F165667C5;
+const PRIME_MX1: u64 = 0x165667919E3779F9;
+const PRIME_MX2: u64 = 0x9FB21C651E98DF25;
+
+/// Inputs up to this size take the short input paths of XXH3
+pub(crate) const MIDSIZE_MAX: usize = 240;
+const MIDSIZE_STARTOFFSET: usize = 3;
+const MIDSIZE_LASTOFFSET: usize = 17;
+pub(crate) const SECRET_SIZE_MIN: usize = 136;
+pub(crate) const SECRET_DEFAULT_SIZE: usize = 192;
+pub(crate) const STRIPE_LEN: usize = 64;
+pub(crate) const SECRET_CONSUME_RATE: usize = 8;
+const SECRET_MERGEACCS_START: usize = 11;
+pub(crate) const SECRET_LASTACC_START: usize = 7;
+
+pub(crate) const INIT_ACC: [u64; 8] = [
+    PRIME32_3 as u64,
+    PRIME64_1,
+    PRIME64_2,
+    PRIME64_3,
+    PRIME64_4,
+    PRIME32_2 as u64,
+    PRIME64_5,
+    PRIME32_1 as u64,
+];
+
+/// The default entropy pool used by the unseeded and seeded variants (XXH3_kSecret)
+#[rustfmt::skip]
+pub(crate) const K_SECRET: [u8; SECRET_DEFAULT_SIZE] = [
+    0xb8, 0xfe, 0x6c, 0x39, 0x23, 0xa4, 0x4b, 0xbe, 0x7c, 0x01, 0x81, 0x2c, 0xf7, 0x21, 0xad, 0x1c,
+    0xde, 0xd4, 0x6d, 0xe9, 0x83, 0x90, 0x97, 0xdb, 0x72, 0x40, 0xa4, 0xa4, 0xb7, 0xb3, 0x67, 0x1f,
+    0xcb, 0x79, 0xe6, 0x4e, 0xcc, 0xc0, 0xe5, 0x78, 0x82, 0x5a, 0xd0, 0x7d, 0xcc, 0xff, 0x72, 0x21,
+    0xb8, 0x08, 0x46, 0x74, 0xf7, 0x43, 0x24, 0x8e, 0xe0, 0x35, 0x90, 0xe6, 0x81, 0x3a, 0x26, 0x4c,
+    0x3c, 0x28, 0x52, 0xbb, 0x91, 0xc3, 0x00, 0xcb, 0x88, 0xd0, 0x65, 0x8b, 0x1b, 0x53, 0x2e, 0xa3,
+    0x71, 0x64, 0x48, 0x97, 0xa2, 0x0d, 0xf9, 0x4e, 0x38, 0x19, 0xef, 0x46, 0xa9, 0xde, 0xac, 0xd8,
+    0xa8, 0xfa, 0x76, 0x3f, 0xe3, 0x9c, 0x34, 0x3f, 0xf9, 0xdc, 0xbb, 0xc7, 0xc7, 0x0b, 0x4f, 0x1d,
+    0x8a, 0x51, 0xe0, 0x4b, 0xcd, 0xb4, 0x59, 0x31, 0xc8, 0x9f, 0x7e, 0xc9, 0xd9, 0x78, 0x73, 0x64,
+    0xea, 0xc5, 0xac, 0x83, 0x34, 0xd3, 0xeb, 0xc3, 0xc5, 0x81, 0xa0, 0xff, 0xfa, 0x13, 0x63, 0xeb,
+    0x17, 0x0d, 0xdd, 0x51, 0xb7, 0xf0, 0xda, 0x49, 0xd3, 0x16, 0x55, 0x26, 0x29, 0xd4, 0x68, 0x9e,
+    0x2b, 0x16, 0xbe, 0x58, 0x7d, 0x47, 0xa1, 0xfc, 0x8f, 0xf8, 0xb8, 0xd1, 0x7a, 0xd0, 0x31, 0xce,
+    0x45, 0xcb, 0x3a, 0x8f, 0x95, 0x16, 0x04, 0x28, 0xaf, 0xd7, 0xfb, 0xca, 0xbb, 0x4b, 0x40, 0x7e,
+];
+
+// split_at() and first_chunk() are const since Rust 1.77, which is why the
+// crate requires it; loading the bytes one by one is not combined into a
+// single load inside loops and makes the 129 to 240 byte path much slower.
+#[inline(always)]
+pub(crate) const fn read32(b: &[u8], off: usize) -> u32 {
+    match b.split_at(off).1.first_chunk() {
+        Some(bytes) => u32::from_le_bytes(*bytes),
+        None => panic!("read32 out of bounds"),
+    }
+}
+
+#[inline(always)]
+pub(crate) const fn read64(b: &[u8], off: usize) -> u64 {
+    match b.split_at(off).1.first_chunk() {
+        Some(bytes) => u64::from_le_bytes(*bytes),
+        None => panic!("read64 out of bounds"),
+    }
+}
+
+#[inline(always)]
+const fn mult64to128(lhs: u64, rhs: u64) -> (u64, u64) {
+    let product = lhs as u128 * rhs as u128;
+    (product as u64, (product >> 64) as u64)
+}
+
+#[inline(always)]
+const fn mul128_fold64(lhs: u64, rhs: u64) -> u64 {
+    let (lo, hi) = mult64to128(lhs, rhs);
+    lo ^ hi
+}
+
+#[inline(always)]
+const fn to_u128(lo: u64, hi: u64) -> u128 {
+    (lo as u128) | (hi as u128) << 64
+}
+
+// XXH32 //
+
+#[inline(always)]
+const fn xxh32_round(acc: u32, input: u32) -> u32 {
+    acc.wrapping_add(input.wrapping_mul(PRIME32_2))
+        .rotate_left(13)
+        .wrapping_mul(PRIME32_1)
+}
+
+#[inline(always)]
+pub(crate) const fn xxh32_init(seed: u32) -> [u32; 4] {
+    [
+        seed.wrapping_add(PRIME32_1).wrapping_add(PRIME32_2),
+        seed.wrapping_add(PRIME32_2),
+        seed,
+        seed.wrapping_sub(PRIME32_1),
+    ]
+}
+
+/// Process the 16 byte stripe at `off`
+#[inline(always)]
+pub(crate) const fn xxh32_stripe(v: [u32; 4], input: &[u8], off: usize) -> [u32; 4] {
+    [
+        xxh32_round(v[0], read32(input, off)),
+        xxh32_round(v[1], read32(input, off + 4)),
+        xxh32_round(v[2], read32(input, off + 8)),
+        xxh32_round(v[3], read32(input, off + 12)),
+    ]
+}
+
+#[inline(always)]
+pub(crate) const fn xxh32_converge(v: &[u32; 4]) -> u32 {
+    v[0].rotate_left(1)
+        .wrapping_add(v[1].rotate_left(7))
+        .wrapping_add(v[2].rotate_left(12))
+        .wrapping_add(v[3].rotate_left(18))
+}
+
+/// Mix in the input from `off` on, less than a stripe, and avalanche
+pub(crate) const fn xxh32_finalize(mut h: u32, input: &[u8], mut off: usize) -> u32 {
+    let len = input.len();
+    while off + 4 <= len {
+        h = h.wrapping_add(read32(input, off).wrapping_mul(PRIME32_3));
+        h = h.rotate_left(17).wrapping_mul(PRIME32_4);
+        off += 4;
+    }
+    while off < len {
+        h = h.wrapping_add((input[off] as u32).wrapping_mul(PRIME32_5));
+        h = h.rotate_left(11).wrapping_mul(PRIME32_1);
+        off += 1;
+    }
+
+    h ^= h >> 15;
+    h = h.wrapping_mul(PRIME32_2);
+    h ^= h >> 13;
+    h = h.wrapping_mul(PRIME32_3);
+    h ^ (h >> 16)
+}
+
+pub(crate) const fn xxh32(input: &[u8], seed: u32) -> u32 {
+    let len = input.len();
+    let mut off = 0;
+    let h = if len >= 16 {
+        let mut v = xxh32_init(seed);
+        while off + 16 <= len {
+            v = xxh32_stripe(v, input, off);
+            off += 16;
+        }
+        xxh32_converge(&v)
+    } else {
+        seed.wrapping_add(PRIME32_5)
+    };
+    xxh32_finalize(h.wrapping_add(len as u32), input, off)
+}
+
+// XXH64 //
+
+#[inline(always)]
+const fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+#[inline(always)]
+const fn xxh64_merge_round(acc: u64, val: u64) -> u64 {
+    (acc ^ xxh64_round(0, val))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+#[inline(always)]
+const fn xxh64_avalanche(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(PRIME64_2);
+    h ^= h >> 29;
+    h = h.wrapping_mul(PRIME64_3);
+    h ^ (h >> 32)
+}
+
+#[inline(always)]
+pub(crate) const fn xxh64_init(seed: u64) -> [u64; 4] {
+    [
+        seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+        seed.wrapping_add(PRIME64_2),
+        seed,
+        seed.wrapping_sub(PRIME64_1),
+    ]
+}
+
+/// Process the 32 byte stripe at `off`
+#[inline(always)]
+pub(crate) const fn xxh64_stripe(v: [u64; 4], input: &[u8], off: usize) -> [u64; 4] {
+    [
+        xxh64_round(v[0], read64(input, off)),
+        xxh64_round(v[1], read64(input, off + 8)),
+        xxh64_round(v[2], read64(input, off + 16)),
+        xxh64_round(v[3], read64(input, off + 24)),
+    ]
+}
+
+#[inline(always)]
+pub(crate) const fn xxh64_converge(v: &[u64; 4]) -> u64 {
+    let mut h = v[0]
+        .rotate_left(1)
+        .wrapping_add(v[1].rotate_left(7))
+        .wrapping_add(v[2].rotate_left(12))
+        .wrapping_add(v[3].rotate_left(18));
+    h = xxh64_merge_round(h, v[0]);
+    h = xxh64_merge_round(h, v[1]);
+    h = xxh64_merge_round(h, v[2]);
+    xxh64_merge_round(h, v[3])
+}
+
+/// Mix in the input from `off` on, less than a stripe, and avalanche
+pub(crate) const fn xxh64_finalize(mut h: u64, input: &[u8], mut off: usize) -> u64 {
+    let len = input.len();
+    while off + 8 <= len {
+        h ^= xxh64_round(0, read64(input, off));
+        h = h
+            .rotate_left(27)
+            .wrapping_mul(PRIME64_1)
+            .wrapping_add(PRIME64_4);
+        off += 8;
+    }
+    if off + 4 <= len {
+        h ^= (read32(input, off) as u64).wrapping_mul(PRIME64_1);
+        h = h
+            .rotate_left(23)
+            .wrapping_mul(PRIME64_2)
+            .wrapping_add(PRIME64_3);
+        off += 4;
+    }
+    while off < len {
+        h ^= (input[off] as u64).wrapping_mul(PRIME64_5);
+        h = h.rotate_left(11).wrapping_mul(PRIME64_1);
+        off += 1;
+    }
+    xxh64_avalanche(h)
+}
+
+pub(crate) const fn xxh64(input: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    let mut off = 0;
+    let h = if len >= 32 {
+        let mut v = xxh64_init(seed);
+        while off + 32 <= len {
+            v = xxh64_stripe(v, input, off);
+            off += 32;
+        }
+        xxh64_converge(&v)
+    } else {
+        seed.wrapping_add(PRIME64_5)
+    };
+    xxh64_finalize(h.wrapping_add(len as u64), input, off)
+}
+
+// XXH3 common //
+
+#[inline(always)]
+const fn xorshift64(v: u64, shift: u32) -> u64 {
+    v ^ (v >> shift)
+}
+
+#[inline(always)]
+const fn avalanche(mut h: u64) -> u64 {
+    h = xorshift64(h, 37);
+    h = h.wrapping_mul(PRIME_MX1);
+    xorshift64(h, 32)
+}
+
+#[inline(always)]
+const fn rrmxmx(mut h: u64, len: u64) -> u64 {
+    h ^= h.rotate_left(49) ^ h.rotate_left(24);
+    h = h.wrapping_mul(PRIME_MX2);
+    h ^= (h >> 35).wrapping_add(len);
+    h = h.wrapping_mul(PRIME_MX2);
+    xorshift64(h, 28)
+}
+
+#[inline(always)]
+const fn mix16b(input: &[u8], ioff: usize, secret: &[u8], soff: usize, seed: u64) -> u64 {
+    mul128_fold64(
+        read64(input, ioff) ^ read64(secret, soff).wrapping_add(seed),
+        read64(input, ioff + 8) ^ read64(secret, soff + 8).wrapping_sub(seed),
+    )
+}
+
+/// Derive the secret used for long inputs by the seeded variants
+pub(crate) const fn init_custom_secret(seed: u64) -> [u8; SECRET_DEFAULT_SIZE] {
+    let mut r = [0u8; SECRET_DEFAULT_SIZE];
+    let mut off = 0;
+    while off < SECRET_DEFAULT_SIZE {
+        let lo = read64(&K_SECRET, off).wrapping_add(seed).to_le_bytes();
+        let hi = read64(&K_SECRET, off + 8).wrapping_sub(seed).to_le_bytes();
+        let mut idx = 0;
+        while idx < 8 {
+            r[off + idx] = lo[idx];
+            r[off + 8 + idx] = hi[idx];
+            idx += 1;
+        }
+        off += 16;
+    }
+    r
+}
+
+/// Derive an entropy pool from a key like XXH3_generateSecret of xxhash
+/// 0.8.0 does; later versions use a different derivation
+pub(crate) const fn generate_secret_v080(key: &[u8]) -> [u8; SECRET_DEFAULT_SIZE] {
+    if key.is_empty() {
+        return K_SECRET;
+    }
+
+    // Twelve seeds taken from the key, truncating or repeating it
+    let mut seeds = [0u8; 96];
+    let mut idx = 0;
+    while idx < seeds.len() {
+        seeds[idx] = key[idx % key.len()];
+        idx += 1;
+    }
+
+    let scrambler = xxh3_128_with_seed(key, 0).to_be_bytes();
+    let mut r = [0u8; SECRET_DEFAULT_SIZE];
+    let mut seg = 0;
+    while seg < SECRET_DEFAULT_SIZE / 16 {
+        let segment = if seg == 0 {
+            scrambler
+        } else {
+            let seed = read64(&seeds, seg * 8).wrapping_add(seg as u64);
+            xxh3_128_with_seed(&scrambler, seed).to_be_bytes()
+        };
+        idx = 0;
+        while idx < 16 {
+            r[seg * 16 + idx] = segment[idx];
+            idx += 1;
+        }
+        seg += 1;
+    }
+    r
+}
+
+// XXH3 long inputs //
+
+#[inline(always)]
+pub(crate) const fn accumulate_512(
+    mut acc: [u64; 8],
+    input: &[u8],
+    ioff: usize,
+    secret: &[u8],
+    soff: usize,
+) -> [u64; 8] {
+    let mut idx = 0;
+    while idx < 8 {
+        let data_val = read64(input, ioff + idx * 8);
+        let data_key = data_val ^ read64(secret, soff + idx * 8);
+        acc[idx ^ 1] = acc[idx ^ 1].wrapping_add(data_val);
+        acc[idx] = acc[idx].wrapping_add((data_key & 0xFFFFFFFF).wrapping_mul(data_key >> 32));
+        idx += 1;
+    }
+    acc
+}
+
+/// Accumulate `nb_stripes` stripes of input starting at `ioff`, using the
+/// secret from `soff` on
+#[inline(always)]
+pub(crate) const fn accumulate(
+    mut acc: [u64; 8],
+    input: &[u8],
+    ioff: usize,
+    secret: &[u8],
+    soff: usize,
+    nb_stripes: usize,
+) -> [u64; 8] {
+    let mut n = 0;
+    while n < nb_stripes {
+        acc = accumulate_512(
+            acc,
+            input,
+            ioff + n * STRIPE_LEN,
+            secret,
+            soff + n * SECRET_CONSUME_RATE,
+        );
+        n += 1;
+    }
+    acc
+}
+
+/// Scramble the accumulators with the secret at `soff`; xxhash uses the
+/// last stripe of the secret
+#[inline(always)]
+pub(crate) const fn scramble(mut acc: [u64; 8], secret: &[u8], soff: usize) -> [u64; 8] {
+    let mut idx = 0;
+    while idx < 8 {
+        let key = read64(secret, soff + idx * 8);
+        acc[idx] = (xorshift64(acc[idx], 47) ^ key).wrapping_mul(PRIME32_1 as u64);
+        idx += 1;
+    }
+    acc
+}
+
+const fn merge_accs(acc: &[u64; 8], secret: &[u8], soff: usize, start: u64) -> u64 {
+    let mut r = start;
+    let mut idx = 0;
+    while idx < 4 {
+        r = r.wrapping_add(mul128_fold64(
+            acc[idx * 2] ^ read64(secret, soff + idx * 16),
+            acc[idx * 2 + 1] ^ read64(secret, soff + idx * 16 + 8),
+        ));
+        idx += 1;
+    }
+    avalanche(r)
+}
+
+/// Accumulators for an input of more than MIDSIZE_MAX bytes
+pub(crate) const fn hash_long(input: &[u8], secret: &[u8]) -> [u64; 8] {
+    let len = input.len();
+    let stripes_per_block = (secret.len() - STRIPE_LEN) / SECRET_CONSUME_RATE;
+    let block_len = STRIPE_LEN * stripes_per_block;
+    let nb_blocks = (len - 1) / block_len;
+    let scramble_off = secret.len() - STRIPE_LEN;
+
+    let mut acc = INIT_ACC;
+    let mut block = 0;
+    while block < nb_blocks {
+        acc = accumulate(acc, input, block * block_len, secret, 0, stripes_per_block);
+        acc = scramble(acc, secret, scramble_off);
+        block += 1;
+    }
+
+    // Last partial block
+    let nb_stripes = ((len - 1) - (block_len * nb_blocks)) / STRIPE_LEN;
+    acc = accumulate(acc, input, nb_blocks * block_len, secret, 0, nb_stripes);
+
+    // Last stripe
+    let soff = secret.len() - STRIPE_LEN - SECRET_LASTACC_START;
+    accumulate_512(acc, input, len - STRIPE_LEN, secret, soff)
+}
+
+#[inline(always)]
+pub(crate) const fn finalize_long_64(acc: &[u64; 8], len: u64, secret: &[u8]) -> u64 {
+    merge_accs(
+        acc,
+        secret,
+        SECRET_MERGEACCS_START,
+        len.wrapping_mul(PRIME64_1),
+    )
+}
+
+#[inline(always)]
+pub(crate) const fn finalize_long_128(acc: &[u64; 8], len: u64, secret: &[u8]) -> u128 {
+    let lo = finalize_long_64(acc, len, secret);
+    let hi = merge_accs(
+        acc,
+        secret,
+        secret.len() - STRIPE_LEN - SECRET_MERGEACCS_START,
+        !len.wrapping_mul(PRIME64_2),
+    );
+    to_u128(lo, hi)
+}
+
+// XXH3 64 bit //
+
+#[inline(always)]
+const fn len_1to3_64(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    let c1 = input[0] as u32;
+    let c2 = input[len >> 1] as u32;
+    let c3 = input[len - 1] as u32;
+    let combined = (c1 << 16) | (c2 << 24) | c3 | ((len as u32) << 8);
+    let bitflip = ((read32(secret, 0) ^ read32(secret, 4)) as u64).wrapping_add(seed);
+    xxh64_avalanche(combined as u64 ^ bitflip)
+}
+
+#[inline(always)]
+const fn len_4to8_64(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    let seed = seed ^ ((seed as u32).swap_bytes() as u64) << 32;
+    let input1 = read32(input, 0) as u64;
+    let input2 = read32(input, len - 4) as u64;
+    let bitflip = (read64(secret, 8) ^ read64(secret, 16)).wrapping_sub(seed);
+    let input64 = input2.wrapping_add(input1 << 32);
+    rrmxmx(input64 ^ bitflip, len as u64)
+}
+
+#[inline(always)]
+const fn len_9to16_64(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    let bitflip1 = (read64(secret, 24) ^ read64(secret, 32)).wrapping_add(seed);
+    let bitflip2 = (read64(secret, 40) ^ read64(secret, 48)).wrapping_sub(seed);
+    let input_lo = read64(input, 0) ^ bitflip1;
+    let input_hi = read64(input, len - 8) ^ bitflip2;
+    let acc = (len as u64)
+        .wrapping_add(input_lo.swap_bytes())
+        .wrapping_add(input_hi)
+        .wrapping_add(mul128_fold64(input_lo, input_hi));
+    avalanche(acc)
+}
+
+#[inline(always)]
+const fn len_0to16_64(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    if len > 8 {
+        len_9to16_64(input, secret, seed)
+    } else if len >= 4 {
+        len_4to8_64(input, secret, seed)
+    } else if len > 0 {
+        len_1to3_64(input, secret, seed)
+    } else {
+        xxh64_avalanche(seed ^ read64(secret, 56) ^ read64(secret, 64))
+    }
+}
+
+#[inline(always)]
+const fn len_17to128_64(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+    if len > 32 {
+        if len > 64 {
+            if len > 96 {
+                acc = acc.wrapping_add(mix16b(input, 48, secret, 96, seed));
+                acc = acc.wrapping_add(mix16b(input, len - 64, secret, 112, seed));
+            }
+            acc = acc.wrapping_add(mix16b(input, 32, secret, 64, seed));
+            acc = acc.wrapping_add(mix16b(input, len - 48, secret, 80, seed));
+        }
+        acc = acc.wrapping_add(mix16b(input, 16, secret, 32, seed));
+        acc = acc.wrapping_add(mix16b(input, len - 32, secret, 48, seed));
+    }
+    acc = acc.wrapping_add(mix16b(input, 0, secret, 0, seed));
+    acc = acc.wrapping_add(mix16b(input, len - 16, secret, 16, seed));
+    avalanche(acc)
+}
+
+#[inline(never)]
+const fn len_129to240_64(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+    let mut i = 0;
+    while i < 8 {
+        acc = acc.wrapping_add(mix16b(input, 16 * i, secret, 16 * i, seed));
+        i += 1;
+    }
+    let last = SECRET_SIZE_MIN - MIDSIZE_LASTOFFSET;
+    let mut acc_end = mix16b(input, len - 16, secret, last, seed);
+    acc = avalanche(acc);
+    while i < len / 16 {
+        let s = 16 * (i - 8) + MIDSIZE_STARTOFFSET;
+        acc_end = acc_end.wrapping_add(mix16b(input, 16 * i, secret, s, seed));
+        i += 1;
+    }
+    avalanche(acc.wrapping_add(acc_end))
+}
+
+/// XXH3 64 bit for inputs of up to MIDSIZE_MAX bytes.
+///
+/// The secret must be at least 136 bytes.
+#[inline]
+pub(crate) const fn xxh3_64_short(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    debug_assert!(input.len() <= MIDSIZE_MAX);
+    debug_assert!(secret.len() >= SECRET_SIZE_MIN);
+    if input.len() <= 16 {
+        len_0to16_64(input, secret, seed)
+    } else if input.len() <= 128 {
+        len_17to128_64(input, secret, seed)
+    } else {
+        len_129to240_64(input, secret, seed)
+    }
+}
+
+pub(crate) const fn xxh3_64_with_secret(input: &[u8], secret: &[u8]) -> u64 {
+    if input.len() <= MIDSIZE_MAX {
+        xxh3_64_short(input, secret, 0)
+    } else {
+        finalize_long_64(&hash_long(input, secret), input.len() as u64, secret)
+    }
+}
+
+pub(crate) const fn xxh3_64_with_seed(input: &[u8], seed: u64) -> u64 {
+    if input.len() <= MIDSIZE_MAX {
+        xxh3_64_short(input, &K_SECRET, seed)
+    } else if seed == 0 {
+        xxh3_64_with_secret(input, &K_SECRET)
+    } else {
+        xxh3_64_with_secret(input, &init_custom_secret(seed))
+    }
+}
+
+// XXH3 128 bit //
+
+#[inline(always)]
+const fn len_1to3_128(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    let len = input.len();
+    let c1 = input[0] as u32;
+    let c2 = input[len >> 1] as u32;
+    let c3 = input[len - 1] as u32;
+    let combinedl = (c1 << 16) | (c2 << 24) | c3 | ((len as u32) << 8);
+    let combinedh = combinedl.swap_bytes().rotate_left(13);
+    let bitflipl = ((read32(secret, 0) ^ read32(secret, 4)) as u64).wrapping_add(seed);
+    let bitfliph = ((read32(secret, 8) ^ read32(secret, 12)) as u64).wrapping_sub(seed);
+    to_u128(
+        xxh64_avalanche(combinedl as u64 ^ bitflipl),
+        xxh64_avalanche(combinedh as u64 ^ bitfliph),
+    )
+}
+
+#[inline(always)]
+const fn len_4to8_128(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    let len = input.len();
+    let seed = seed ^ ((seed as u32).swap_bytes() as u64) << 32;
+    let input_lo = read32(input, 0) as u64;
+    let input_hi = read32(input, len - 4) as u64;
+    let input64 = input_lo.wrapping_add(input_hi << 32);
+    let bitflip = (read64(secret, 16) ^ read64(secret, 24)).wrapping_add(seed);
+    let keyed = input64 ^ bitflip;
+
+    let (mut lo, mut hi) = mult64to128(keyed, PRIME64_1.wrapping_add((len as u64) << 2));
+    hi = hi.wrapping_add(lo << 1);
+    lo ^= hi >> 3;
+
+    lo = xorshift64(lo, 35);
+    lo = lo.wrapping_mul(PRIME_MX2);
+    lo = xorshift64(lo, 28);
+    hi = avalanche(hi);
+    to_u128(lo, hi)
+}
+
+#[inline(always)]
+const fn len_9to16_128(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    let len = input.len();
+    let bitflipl = (read64(secret, 32) ^ read64(secret, 40)).wrapping_sub(seed);
+    let bitfliph = (read64(secret, 48) ^ read64(secret, 56)).wrapping_add(seed);
+    let input_lo = read64(input, 0);
+    let mut input_hi = read64(input, len - 8);
+    let (mut m_lo, mut m_hi) = mult64to128(input_lo ^ input_hi ^ bitflipl, PRIME64_1);
+    m_lo = m_lo.wrapping_add(((len - 1) as u64) << 54);
+    input_hi ^= bitfliph;
+    m_hi = m_hi
+        .wrapping_add(input_hi)
+        .wrapping_add((input_hi as u32 as u64) * (PRIME32_2 - 1) as u64);
+    m_lo ^= m_hi.swap_bytes();
+
+    let (lo, mut hi) = mult64to128(m_lo, PRIME64_2);
+    hi = hi.wrapping_add(m_hi.wrapping_mul(PRIME64_2));
+    to_u128(avalanche(lo), avalanche(hi))
+}
+
+#[inline(always)]
+const fn len_0to16_128(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    let len = input.len();
+    if len > 8 {
+        len_9to16_128(input, secret, seed)
+    } else if len >= 4 {
+        len_4to8_128(input, secret, seed)
+    } else if len > 0 {
+        len_1to3_128(input, secret, seed)
+    } else {
+        let bitflipl = read64(secret, 64) ^ read64(secret, 72);
+        let bitfliph = read64(secret, 80) ^ read64(secret, 88);
+        to_u128(
+            xxh64_avalanche(seed ^ bitflipl),
+            xxh64_avalanche(seed ^ bitfliph),
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+const fn mix32b(
+    acc: (u64, u64),
+    input: &[u8],
+    ioff1: usize,
+    ioff2: usize,
+    secret: &[u8],
+    soff: usize,
+    seed: u64,
+) -> (u64, u64) {
+    let (mut lo, mut hi) = acc;
+    lo = lo.wrapping_add(mix16b(input, ioff1, secret, soff, seed));
+    lo ^= read64(input, ioff2).wrapping_add(read64(input, ioff2 + 8));
+    hi = hi.wrapping_add(mix16b(input, ioff2, secret, soff + 16, seed));
+    hi ^= read64(input, ioff1).wrapping_add(read64(input, ioff1 + 8));
+    (lo, hi)
+}
+
+#[inline(always)]
+const fn finalize_128(acc: (u64, u64), len: usize, seed: u64) -> u128 {
+    let (lo, hi) = acc;
+    let h_lo = lo.wrapping_add(hi);
+    let h_hi = lo
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(hi.wrapping_mul(PRIME64_4))
+        .wrapping_add((len as u64).wrapping_sub(seed).wrapping_mul(PRIME64_2));
+    to_u128(avalanche(h_lo), 0u64.wrapping_sub(avalanche(h_hi)))
+}
+
+#[inline(always)]
+const fn len_17to128_128(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    let len = input.len();
+    let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0);
+    if len > 32 {
+        if len > 64 {
+            if len > 96 {
+                acc = mix32b(acc, input, 48, len - 64, secret, 96, seed);
+            }
+            acc = mix32b(acc, input, 32, len - 48, secret, 64, seed);
+        }
+        acc = mix32b(acc, input, 16, len - 32, secret, 32, seed);
+    }
+    acc = mix32b(acc, input, 0, len - 16, secret, 0, seed);
+    finalize_128(acc, len, seed)
+}
+
+#[inline(never)]
+const fn len_129to240_128(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    let len = input.len();
+    let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0);
+    let mut i = 32;
+    while i < 160 {
+        acc = mix32b(acc, input, i - 32, i - 16, secret, i - 32, seed);
+        i += 32;
+    }
+    acc = (avalanche(acc.0), avalanche(acc.1));
+    while i <= len {
+        let s = MIDSIZE_STARTOFFSET + i - 160;
+        acc = mix32b(acc, input, i - 32, i - 16, secret, s, seed);
+        i += 32;
+    }
+    let last = SECRET_SIZE_MIN - MIDSIZE_LASTOFFSET - 16;
+    let neg_seed = 0u64.wrapping_sub(seed);
+    acc = mix32b(acc, input, len - 16, len - 32, secret, last, neg_seed);
+    finalize_128(acc, len, seed)
+}
+
+/// XXH3 128 bit for inputs of up to MIDSIZE_MAX bytes.
+///
+/// The secret must be at least 136 bytes.
+#[inline]
+pub(crate) const fn xxh3_128_short(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    debug_assert!(input.len() <= MIDSIZE_MAX);
+    debug_assert!(secret.len() >= SECRET_SIZE_MIN);
+    if input.len() <= 16 {
+        len_0to16_128(input, secret, seed)
+    } else if input.len() <= 128 {
+        len_17to128_128(input, secret, seed)
+    } else {
+        len_129to240_128(input, secret, seed)
+    }
+}
+
+pub(crate) const fn xxh3_128_with_secret(input: &[u8], secret: &[u8]) -> u128 {
+    if input.len() <= MIDSIZE_MAX {
+        xxh3_128_short(input, secret, 0)
+    } else {
+        finalize_long_128(&hash_long(input, secret), input.len() as u64, secret)
+    }
+}
+
+pub(crate) const fn xxh3_128_with_seed(input: &[u8], seed: u64) -> u128 {
+    if input.len() <= MIDSIZE_MAX {
+        xxh3_128_short(input, &K_SECRET, seed)
+    } else if seed == 0 {
+        xxh3_128_with_secret(input, &K_SECRET)
+    } else {
+        xxh3_128_with_secret(input, &init_custom_secret(seed))
+    }
+}
+
+// Exports //
+
+/// Compile time version of `XXH32::hash()`.
+///
+/// ```
+/// const TAG: u32 = xxhrs::const_xxh32(b"MyData");
+/// assert_eq!(TAG, xxhrs::XXH32::hash(b"MyData"));
+/// ```
+pub const fn const_xxh32(bytes: &[u8]) -> u32 {
+    xxh32(bytes, 0)
+}
+
+/// Compile time version of `XXH32::hash_with_seed()`.
+pub const fn const_xxh32_with_seed(seed: u32, bytes: &[u8]) -> u32 {
+    xxh32(bytes, seed)
+}
+
+/// Compile time version of `XXH64::hash()`.
+pub const fn const_xxh64(bytes: &[u8]) -> u64 {
+    xxh64(bytes, 0)
+}
+
+/// Compile time version of `XXH64::hash_with_seed()`.
+pub const fn const_xxh64_with_seed(seed: u64, bytes: &[u8]) -> u64 {
+    xxh64(bytes, seed)
+}
+
+/// Compile time version of `XXH3_64::hash()`.
+///
+/// ```
+/// const TAG: u64 = xxhrs::const_xxh3_64(b"foo");
+/// assert_eq!(TAG, xxhrs::XXH3_64::hash(b"foo"));
+/// ```
+pub const fn const_xxh3_64(bytes: &[u8]) -> u64 {
+    xxh3_64_with_seed(bytes, 0)
+}
+
+/// Compile time version of `XXH3_64::hash_with_seed()`.
+pub const fn const_xxh3_64_with_seed(seed: u64, bytes: &[u8]) -> u64 {
+    xxh3_64_with_seed(bytes, seed)
+}
+
+/// Compile time version of `XXH3_128::hash()`.
+pub const fn const_xxh3_128(bytes: &[u8]) -> u128 {
+    xxh3_128_with_seed(bytes, 0)
+}
+
+/// Compile time version of `XXH3_128::hash_with_seed()`.
+pub const fn const_xxh3_128_with_seed(seed: u64, bytes: &[u8]) -> u128 {
+    xxh3_128_with_seed(bytes, seed)
+}
@@ -0,0 +1,170 @@
+use crate::{error::Error, xxh3::xxh128_to_u128, C};
+use core::{fmt, str::FromStr};
+
+/// Parse exactly `N * 2` hex digits into the big endian byte array.
+///
+/// This is the canonical hexadecimal representation, as produced by
+/// the Display implementation and by xxhsum.
+fn parse_canonical<const N: usize>(s: &str) -> Result<[u8; N], Error> {
+    if s.len() != N * 2 || !s.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::ParseHash);
+    }
+
+    let mut r = [0u8; N];
+    for (idx, byte) in r.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[idx * 2..idx * 2 + 2], 16).map_err(|_| Error::ParseHash)?;
+    }
+    Ok(r)
+}
+
+macro_rules! impl_hash_fmt {
+    ($typ:ident, $int:ty, $width:expr) => {
+        impl From<$int> for $typ {
+            #[inline]
+            fn from(v: $int) -> Self {
+                $typ(v)
+            }
+        }
+
+        impl From<$typ> for $int {
+            #[inline]
+            fn from(v: $typ) -> Self {
+                v.0
+            }
+        }
+
+        /// Prints the canonical representation in lower case hex
+        impl fmt::Display for $typ {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{:0w$x}", self.0, w = $width)
+            }
+        }
+
+        impl fmt::LowerHex for $typ {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if f.alternate() {
+                    f.write_str("0x")?;
+                }
+                write!(f, "{:0w$x}", self.0, w = $width)
+            }
+        }
+
+        impl fmt::UpperHex for $typ {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if f.alternate() {
+                    f.write_str("0x")?;
+                }
+                write!(f, "{:0w$X}", self.0, w = $width)
+            }
+        }
+
+        /// Parses the canonical representation; upper and lower case hex are accepted
+        impl FromStr for $typ {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Self::from_canonical(parse_canonical(s)?))
+            }
+        }
+    };
+}
+
+/// Result of the 32 bit xxhash.
+///
+/// Use `to_canonical()` to store or transmit the hash; this produces the
+/// same big endian representation as the C library and xxhsum.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Hash32(pub u32);
+
+impl Hash32 {
+    /// Canonical (big endian) byte representation.
+    ///
+    /// This corresponds to XXH32_canonicalFromHash.
+    #[inline]
+    pub fn to_canonical(self) -> [u8; 4] {
+        let mut r = C::XXH32_canonical_t {
+            digest: Default::default(),
+        };
+        unsafe { C::XXH32_canonicalFromHash(&mut r, self.0) };
+        r.digest
+    }
+
+    /// Parse the canonical (big endian) byte representation.
+    ///
+    /// This corresponds to XXH32_hashFromCanonical.
+    #[inline]
+    pub fn from_canonical(digest: [u8; 4]) -> Self {
+        let c = C::XXH32_canonical_t { digest };
+        Hash32(unsafe { C::XXH32_hashFromCanonical(&c) })
+    }
+}
+
+impl_hash_fmt!(Hash32, u32, 8);
+
+/// Result of the 64 bit xxhash and of xxh3 64 bit.
+///
+/// Use `to_canonical()` to store or transmit the hash; this produces the
+/// same big endian representation as the C library and xxhsum.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Hash64(pub u64);
+
+impl Hash64 {
+    /// Canonical (big endian) byte representation.
+    ///
+    /// This corresponds to XXH64_canonicalFromHash.
+    #[inline]
+    pub fn to_canonical(self) -> [u8; 8] {
+        let mut r = C::XXH64_canonical_t {
+            digest: Default::default(),
+        };
+        unsafe { C::XXH64_canonicalFromHash(&mut r, self.0) };
+        r.digest
+    }
+
+    /// Parse the canonical (big endian) byte representation.
+    ///
+    /// This corresponds to XXH64_hashFromCanonical.
+    #[inline]
+    pub fn from_canonical(digest: [u8; 8]) -> Self {
+        let c = C::XXH64_canonical_t { digest };
+        Hash64(unsafe { C::XXH64_hashFromCanonical(&c) })
+    }
+}
+
+impl_hash_fmt!(Hash64, u64, 16);
+
+/// Result of xxh3 128 bit.
+///
+/// Use `to_canonical()` to store or transmit the hash; this produces the
+/// same big endian representation as the C library and xxhsum.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Hash128(pub u128);
+
+impl Hash128 {
+    /// Canonical (big endian) byte representation.
+    ///
+    /// This corresponds to XXH128_canonicalFromHash.
+    #[inline]
+    pub fn to_canonical(self) -> [u8; 16] {
+        let h = C::XXH128_hash_t {
+            low64: self.0 as u64,
+            high64: (self.0 >> 64) as u64,
+        };
+        let mut r = C::XXH128_canonical_t {
+            digest: Default::default(),
+        };
+        unsafe { C::XXH128_canonicalFromHash(&mut r, h) };
+        r.digest
+    }
+
+    /// Parse the canonical (big endian) byte representation.
+    ///
+    /// This corresponds to XXH128_hashFromCanonical.
+    #[inline]
+    pub fn from_canonical(digest: [u8; 16]) -> Self {
+        let c = C::XXH128_canonical_t { digest };
+        Hash128(xxh128_to_u128(unsafe { C::XXH128_hashFromCanonical(&c) }))
+    }
+}
+
+impl_hash_fmt!(Hash128, u128, 32);
@@ -1,10 +1,14 @@
-use crate::C;
-use std::{fmt, os::raw::c_void};
+use crate::{consthash::generate_secret_v080, error::Error, C};
+use core::{convert::TryFrom, ffi::c_void, fmt};
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec, vec::Vec};
 
 #[cfg(feature = "random_entropy")]
 use getrandom::getrandom;
 
 pub const ENTROPY_POOL_SIZE: usize = C::XXH3_SECRET_DEFAULT_SIZE as usize;
+pub const ENTROPY_POOL_SIZE_MIN: usize = C::XXH3_SECRET_SIZE_MIN as usize;
 
 /// Besides seeded and unseeded variants, XXH3 provides a keyed (`withSecret`)
 /// variant. This is a bit of a misnomer though, because these functions havea
@@ -60,27 +64,152 @@ impl EntropyPool {
     }
 
     /// Use the getrandom crate to create a randomized EntropyPool
+    ///
+    /// Panics if no random entropy is available; use try_randomize()
+    /// to handle that case.
     #[inline]
     #[cfg(feature = "random_entropy")]
     pub fn randomize() -> Self {
+        Self::try_randomize().unwrap()
+    }
+
+    /// Use the getrandom crate to create a randomized EntropyPool
+    #[inline]
+    #[cfg(feature = "random_entropy")]
+    pub fn try_randomize() -> Result<Self, Error> {
         let mut r = Self::new();
-        getrandom(&mut r.entropy).unwrap();
+        getrandom(&mut r.entropy)?;
 
-        r
+        Ok(r)
     }
 
     /// Use the higher quality entropy derivation scheme from variable
     /// length input keys
+    ///
+    /// This is the derivation of XXH3_generateSecret in xxhash 0.8.0, so
+    /// the entropy pools and the hashes produced with them stay the same
+    /// across xxhrs versions. Newer xxhash versions derive different
+    /// pools; see CustomEntropyPool::with_key().
     #[inline]
     pub fn with_key(key: &[u8]) -> Self {
+        Self {
+            entropy: generate_secret_v080(key),
+        }
+    }
+
+    /// Derive the entropy pool the seeded hash functions use internally.
+    ///
+    /// This corresponds to XXH3_generateSecret_fromSeed. Hashing with
+    /// `hash_with_entropy_and_seed(&EntropyPool::from_seed(seed), seed, …)`
+    /// produces the same hashes as `hash_with_seed(seed, …)`, but avoids
+    /// deriving the entropy pool again for every long input.
+    ///
+    /// `hash_with_entropy(&EntropyPool::from_seed(seed), …)` is only
+    /// guaranteed to match `hash_with_seed(seed, …)` for inputs longer than
+    /// 240 bytes; shorter inputs are hashed differently.
+    #[inline]
+    pub fn from_seed(seed: u64) -> Self {
         let mut r = Self::new();
+        unsafe {
+            C::XXH3_generateSecret_fromSeed(r.entropy.as_mut_ptr() as *mut c_void, seed);
+        }
+        r
+    }
+}
+
+/// Copy exactly ENTROPY_POOL_SIZE bytes of entropy
+impl TryFrom<&[u8]> for EntropyPool {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(entropy: &[u8]) -> Result<Self, Error> {
+        let mut r = Self::new();
+        if entropy.len() != r.entropy.len() {
+            return Err(Error::EntropyPoolSize(entropy.len()));
+        }
+        r.entropy.copy_from_slice(entropy);
+        Ok(r)
+    }
+}
+
+/// Entropy pool of custom size.
+///
+/// This works just like EntropyPool, but supports any size of at least
+/// ENTROPY_POOL_SIZE_MIN (136) bytes instead of exactly ENTROPY_POOL_SIZE.
+/// The size is checked once on construction, so the functions taking a
+/// CustomEntropyPool are safe to use.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CustomEntropyPool {
+    entropy: Box<[u8]>,
+}
+
+#[cfg(feature = "alloc")]
+impl CustomEntropyPool {
+    /// Use the given bytes as entropy pool
+    #[inline]
+    pub fn new(entropy: Vec<u8>) -> Result<Self, Error> {
+        if entropy.len() < ENTROPY_POOL_SIZE_MIN {
+            return Err(Error::EntropyPoolSize(entropy.len()));
+        }
+        Ok(Self {
+            entropy: entropy.into_boxed_slice(),
+        })
+    }
+
+    /// Use the higher quality entropy derivation scheme from variable
+    /// length input keys to produce a pool of the given size.
+    ///
+    /// This corresponds to XXH3_generateSecret of xxhash 0.8.1 and later,
+    /// which derives different entropy pools than EntropyPool::with_key(),
+    /// even for a size of ENTROPY_POOL_SIZE.
+    #[inline]
+    pub fn with_key(size: usize, key: &[u8]) -> Result<Self, Error> {
+        let mut r = Self::new(vec![0u8; size])?;
         unsafe {
             C::XXH3_generateSecret(
                 r.entropy.as_mut_ptr() as *mut c_void,
+                r.entropy.len(),
                 key.as_ptr() as *const c_void,
                 key.len(),
             );
         }
-        r
+        Ok(r)
+    }
+
+    /// The raw entropy
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.entropy
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<EntropyPool> for CustomEntropyPool {
+    #[inline]
+    fn from(pool: EntropyPool) -> Self {
+        Self {
+            entropy: Box::new(pool.entropy),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryFrom<&[u8]> for CustomEntropyPool {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(entropy: &[u8]) -> Result<Self, Error> {
+        Self::new(entropy.to_vec())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryFrom<Vec<u8>> for CustomEntropyPool {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(entropy: Vec<u8>) -> Result<Self, Error> {
+        Self::new(entropy)
     }
 }
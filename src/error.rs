@@ -0,0 +1,60 @@
+use crate::backend::Backend;
+use core::fmt;
+
+/// Errors produced by xxhrs
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Gathering random entropy using getrandom failed
+    #[cfg(feature = "random_entropy")]
+    Random(getrandom::Error),
+    /// An entropy pool of invalid size was supplied; the size of the
+    /// rejected entropy pool is given.
+    ///
+    /// EntropyPool requires exactly ENTROPY_POOL_SIZE bytes, CustomEntropyPool
+    /// at least ENTROPY_POOL_SIZE_MIN bytes.
+    EntropyPoolSize(usize),
+    /// A hash could not be parsed from it's hexadecimal representation
+    ParseHash,
+    /// The requested XXH3 backend is not available on this cpu or build
+    UnsupportedBackend(Backend),
+    /// `self_test()` found a hash differing from the expected one; the
+    /// hash function and mode as well as the input length are given.
+    SelfTest(&'static str, usize),
+    /// A saved hasher state could not be restored; it is corrupted, of an
+    /// unknown version or was saved by a different hash function.
+    InvalidSavedState,
+    /// A saved XXH3 state was restored into a hasher using a different
+    /// entropy pool than the one the state was saved with.
+    EntropyPoolMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "random_entropy")]
+            Error::Random(e) => write!(f, "could not gather random entropy: {}", e),
+            Error::EntropyPoolSize(size) => write!(f, "invalid entropy pool size: {} bytes", size),
+            Error::ParseHash => f.write_str("invalid hexadecimal hash representation"),
+            Error::UnsupportedBackend(b) => write!(f, "unsupported xxh3 backend: {:?}", b),
+            Error::SelfTest(what, len) => {
+                write!(f, "xxhash self test failed: {} of {} bytes", what, len)
+            }
+            Error::InvalidSavedState => f.write_str("invalid saved hasher state"),
+            Error::EntropyPoolMismatch => {
+                f.write_str("saved hasher state uses a different entropy pool")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "random_entropy")]
+impl From<getrandom::Error> for Error {
+    #[inline]
+    fn from(e: getrandom::Error) -> Self {
+        Error::Random(e)
+    }
+}
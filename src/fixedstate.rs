@@ -0,0 +1,91 @@
+use crate::{
+    entropy::EntropyPool,
+    xxh3::{XXH3_128, XXH3_64},
+    xxhash::{XXH32, XXH64},
+};
+use core::{default::Default, hash::BuildHasher};
+
+/// xxhash 32 bit version. Uses a fixed seed, so the hashes are the same
+/// in every process.
+/// Because the associated hasher generates u32, this does not implement BuildHash,
+/// however this features the same interface.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct FixedStateXXH32 {
+    pub seed: u32,
+}
+
+impl FixedStateXXH32 {
+    #[inline]
+    pub fn build_hasher(&self) -> XXH32 {
+        XXH32::with_seed(self.seed)
+    }
+}
+
+/// xxhash 64 bit version. Uses a fixed seed, so the hashes are the same
+/// in every process; this is useful for reproducible hash maps or sharding.
+/// Unlike RandomStateXXH64, this provides no protection against HashDoS.
+/// Implements BuildHasher.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct FixedStateXXH64 {
+    pub seed: u64,
+}
+
+impl BuildHasher for FixedStateXXH64 {
+    type Hasher = XXH64;
+
+    #[inline]
+    fn build_hasher(&self) -> Self::Hasher {
+        Self::Hasher::with_seed(self.seed)
+    }
+}
+
+/// xxh3 64 bit version. Uses a fixed entropy pool, so the hashes are the same
+/// in every process. The default uses the entropy pool of the unseeded variant.
+/// Implements BuildHasher.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FixedStateXXH3_64 {
+    pub pool: EntropyPool,
+}
+
+impl Default for FixedStateXXH3_64 {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            pool: EntropyPool::from_seed(0),
+        }
+    }
+}
+
+impl BuildHasher for FixedStateXXH3_64 {
+    type Hasher = XXH3_64<'static>;
+
+    #[inline]
+    fn build_hasher(&self) -> Self::Hasher {
+        Self::Hasher::with_entropy(&self.pool)
+    }
+}
+
+/// xxh3 128 bit version. Uses a fixed entropy pool, so the hashes are the same
+/// in every process. The default uses the entropy pool of the unseeded variant.
+/// Because the associated hasher generates u128, this does not implement BuildHash,
+/// however this features the same interface.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FixedStateXXH3_128 {
+    pub pool: EntropyPool,
+}
+
+impl Default for FixedStateXXH3_128 {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            pool: EntropyPool::from_seed(0),
+        }
+    }
+}
+
+impl FixedStateXXH3_128 {
+    #[inline]
+    pub fn build_hasher(&self) -> XXH3_128<'static> {
+        XXH3_128::with_entropy(&self.pool)
+    }
+}
@@ -0,0 +1,11 @@
+/* Minimal string.h for building xxhash without a libc (no std feature).
+ *
+ * xxhash only needs these three functions; they are mapped to the compiler
+ * builtins, which are either inlined or resolved against the implementations
+ * in rust's compiler_builtins. */
+#pragma once
+#include <stddef.h>
+
+#define memcpy(dst, src, n) __builtin_memcpy((dst), (src), (n))
+#define memset(dst, c, n) __builtin_memset((dst), (c), (n))
+#define memcmp(a, b, n) __builtin_memcmp((a), (b), (n))
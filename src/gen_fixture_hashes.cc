@@ -3,6 +3,7 @@
 #define XXH_INLINE_ALL 1
 #include <cassert>
 #include <cstdint>
+#include <cstring>
 #include <utility>
 #include <array>
 #include <string>
@@ -49,6 +50,24 @@ void pt(const char *name, const XXH128_hash_t &val) {
     << std::setw(16) << val.low64 << ";\n";
 }
 
+// XXH3_generateSecret of xxhash 0.8.0, which EntropyPool::with_key() keeps using
+void generate_secret_v080(uint8_t *secret, const std::vector<uint8_t> &key) {
+  assert(!key.empty());
+  uint8_t seeds[96];
+  for (size_t idx = 0; idx < sizeof(seeds); idx++)
+    seeds[idx] = key[idx % std::size(key)];
+
+  XXH128_canonical_t scrambler;
+  XXH128_canonicalFromHash(&scrambler, XXH128(std::data(key), std::size(key), 0));
+  memcpy(secret, &scrambler, sizeof(scrambler));
+  for (size_t seg = 1; seg < XXH3_SECRET_DEFAULT_SIZE / 16; seg++) {
+    XXH128_canonical_t segment;
+    XXH128_canonicalFromHash(&segment,
+      XXH128(&scrambler, sizeof(scrambler), XXH_readLE64(seeds + seg * 8) + seg));
+    memcpy(secret + seg * 16, &segment, sizeof(segment));
+  }
+}
+
 int main(int argc, const char **argv) {
   assert(argc > 0);
 
@@ -61,15 +80,17 @@ int main(int argc, const char **argv) {
     data = read_file("fixtures/data"),
     key = read_file("fixtures/secret");
 
-  std::array<uint8_t, XXH3_SECRET_DEFAULT_SIZE> secret_entropy, seed_entropy;
-  ::XXH3_initCustomSecret(std::data(seed_entropy), seed64);
-  ::XXH3_generateSecret(std::data(secret_entropy), std::data(key), std::size(key));
+  std::array<uint8_t, XXH3_SECRET_DEFAULT_SIZE> secret_entropy, seed_entropy, custom_secret_entropy;
+  ::XXH3_generateSecret_fromSeed(std::data(seed_entropy), seed64);
+  generate_secret_v080(std::data(secret_entropy), key);
+  ::XXH3_generateSecret(std::data(custom_secret_entropy), std::size(custom_secret_entropy), std::data(key), std::size(key));
 
   write_file("fixtures/secret_entropy", secret_entropy);
   write_file("fixtures/seed64_entropy", seed_entropy);
+  write_file("fixtures/custom_secret_entropy", custom_secret_entropy);
 
   const uint8_t *d = std::data(data), *ed = std::data(secret_entropy);
-  size_t s = std::size(data), es = std::size(secret_entropy);
+  size_t s = std::size(data), es = std::size(secret_entropy), short_s = 100;
   pt("SEED32", seed32);
   pt("SEED64", seed64);
   pt("XXH32_HASH     ", XXH32(d, s, 0));
@@ -82,6 +103,13 @@ int main(int argc, const char **argv) {
   pt("XXH3_128_HASH  ", XXH3_128bits(d, s));
   pt("XXH3_128_SEEDED", XXH3_128bits_withSeed(d, s, seed64));
   pt("XXH3_128_KEYED ", XXH3_128bits_withSecret(d, s, ed, es));
+  pt("XXH3_64_KEYED_SEEDED ", XXH3_64bits_withSecretandSeed(d, s, ed, es, seed64));
+  pt("XXH3_128_KEYED_SEEDED", XXH3_128bits_withSecretandSeed(d, s, ed, es, seed64));
+  // Inputs up to 240 bytes only use the seed, longer inputs only the secret
+  pt("XXH3_64_KEYED_SEEDED_SHORT ", XXH3_64bits_withSecretandSeed(d, short_s, ed, es, seed64));
+  pt("XXH3_128_KEYED_SEEDED_SHORT", XXH3_128bits_withSecretandSeed(d, short_s, ed, es, seed64));
+  pt("XXH3_64_KEYED_SEED0_SHORT  ", XXH3_64bits_withSecretandSeed(d, short_s, ed, es, 0));
+  pt("XXH3_128_KEYED_SEED0_SHORT ", XXH3_128bits_withSecretandSeed(d, short_s, ed, es, 0));
 
   return 0;
 }
@@ -0,0 +1,72 @@
+use core::fmt;
+
+/// Common streaming interface of all the hashers in this crate.
+///
+/// `std::hash::Hasher` can only be implemented by hashers producing
+/// u64, so XXH32 and XXH3_128 can not implement it. This trait is
+/// implemented by all of them, so code can be generic over the
+/// hash function used.
+///
+/// ```
+/// use xxhrs::{StreamingHasher, XXH3_128, XXH64};
+///
+/// fn checksum<H: StreamingHasher>(mut h: H, chunks: &[&[u8]]) -> H::Output {
+///     for c in chunks {
+///         h.write(c);
+///     }
+///     h.finish()
+/// }
+///
+/// assert_eq!(checksum(XXH64::new(), &[b"My", b"Data"]), XXH64::hash(b"MyData"));
+/// assert_eq!(checksum(XXH3_128::new(), &[b"My", b"Data"]), XXH3_128::hash(b"MyData"));
+/// ```
+pub trait StreamingHasher {
+    /// The type of the hash produced
+    type Output;
+
+    /// Feed more data into the hasher
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Produce the hash of the data written so far.
+    ///
+    /// This does not modify the state, so more data can be written
+    /// after calling finish.
+    fn finish(&self) -> Self::Output;
+
+    /// Discard all the data written so far.
+    ///
+    /// The hasher is returned to the state it was in right after construction;
+    /// seed and entropy pool are retained.
+    fn reset(&mut self);
+}
+
+/// How a streaming hasher was configured; see `mode()` of the hashers
+///
+/// Seeds of hashers built by the `RandomState*` types are secret keys;
+/// the `Debug` output of the hashers therefore leaves them out.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Mode {
+    /// No seed (or seed 0) and the default entropy pool
+    Unseeded,
+    /// Seeded; for XXH3 with the entropy pool derived from the seed
+    Seed(u64),
+    /// XXH3 with a custom entropy pool
+    Entropy,
+    /// XXH3 with a custom entropy pool and a seed
+    EntropyAndSeed(u64),
+}
+
+/// Formats a mode without the seed, for the `Debug` output of the hashers
+pub(crate) struct RedactedMode(pub(crate) Mode);
+
+impl fmt::Debug for RedactedMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Mode::Unseeded => f.write_str("Unseeded"),
+            Mode::Seed(_) => f.write_str("Seed(..)"),
+            Mode::Entropy => f.write_str("Entropy"),
+            Mode::EntropyAndSeed(_) => f.write_str("EntropyAndSeed(..)"),
+        }
+    }
+}
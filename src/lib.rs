@@ -1,18 +1,46 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
 #[cfg(doctest)]
 #[macro_use]
 extern crate doc_comment;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 // C code //
 
+#[cfg(not(feature = "pure-rust"))]
 #[allow(non_snake_case)]
 mod C;
+#[cfg(not(feature = "pure-rust"))]
 mod xxhash_bindings;
 
+// Native implementation of the C API; replaces the C code with the
+// pure-rust feature and is tested against it otherwise
+#[cfg(any(feature = "pure-rust", test))]
+#[cfg_attr(not(feature = "pure-rust"), allow(dead_code))]
+#[allow(non_snake_case, non_camel_case_types)]
+mod native;
+#[cfg(feature = "pure-rust")]
+use native as C;
+
 // Rust code //
 
-#[cfg(feature = "random_entropy")]
+mod backend;
+#[cfg(all(feature = "random_entropy", feature = "std"))]
 mod buildhash;
+mod buildinfo;
+mod consthash;
+mod digest;
 mod entropy;
+mod error;
+mod fixedstate;
+mod hasher;
+mod macros;
+#[cfg(all(feature = "random_entropy", feature = "std"))]
+mod masterpool;
+mod savedstate;
+mod selftest;
 mod xxh3;
 mod xxhash;
 
@@ -22,14 +50,25 @@ mod xxhash;
 mod tests;
 
 #[cfg(doctest)]
-#[cfg(feature = "random_entropy")]
+#[cfg(all(feature = "random_entropy", feature = "std"))]
 doctest!("../readme.md");
 
 // Exports //
 
-#[cfg(feature = "random_entropy")]
+#[cfg(all(feature = "random_entropy", feature = "std"))]
 pub use buildhash::*;
 
+pub use backend::*;
+pub use buildinfo::*;
+pub use consthash::*;
+pub use digest::*;
 pub use entropy::*;
+pub use error::*;
+pub use fixedstate::*;
+pub use hasher::*;
+#[doc(hidden)]
+pub use macros::MacroInput as __MacroInput;
+pub use savedstate::{XXH32_SAVED_STATE_SIZE, XXH3_SAVED_STATE_SIZE, XXH64_SAVED_STATE_SIZE};
+pub use selftest::*;
 pub use xxh3::*;
 pub use xxhash::*;
@@ -0,0 +1,98 @@
+// Macros evaluating the const hash functions on literals and files, so
+// the hashes end up in the binary as integer constants.
+
+/// Turns the inputs accepted by the macros (`&str`, byte string
+/// literals and byte slices) into a byte slice in const context.
+#[doc(hidden)]
+pub struct MacroInput<T>(pub T);
+
+impl<'a> MacroInput<&'a str> {
+    pub const fn bytes(self) -> &'a [u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl<'a> MacroInput<&'a [u8]> {
+    pub const fn bytes(self) -> &'a [u8] {
+        self.0
+    }
+}
+
+impl<'a, const N: usize> MacroInput<&'a [u8; N]> {
+    pub const fn bytes(self) -> &'a [u8] {
+        self.0
+    }
+}
+
+/// XXH3 64 bit hash of a string or byte string, computed at compile time.
+///
+/// The argument must be a constant expression.
+///
+/// ```
+/// use xxhrs::{xxh3_64, XXH3_64};
+/// assert_eq!(xxh3_64!("MyData"), XXH3_64::hash(b"MyData"));
+/// assert_eq!(xxh3_64!(b"MyData"), XXH3_64::hash(b"MyData"));
+/// ```
+#[macro_export]
+macro_rules! xxh3_64 {
+    ($input:expr) => {{
+        const HASH: u64 = $crate::const_xxh3_64($crate::__MacroInput($input).bytes());
+        HASH
+    }};
+}
+
+/// XXH3 128 bit hash of a string or byte string, computed at compile time.
+///
+/// The argument must be a constant expression.
+///
+/// ```
+/// use xxhrs::{xxh3_128, XXH3_128};
+/// const TAG: u128 = xxh3_128!(b"MyData");
+/// assert_eq!(TAG, XXH3_128::hash(b"MyData"));
+/// ```
+#[macro_export]
+macro_rules! xxh3_128 {
+    ($input:expr) => {{
+        const HASH: u128 = $crate::const_xxh3_128($crate::__MacroInput($input).bytes());
+        HASH
+    }};
+}
+
+/// XXH3 64 bit hash of a file, computed at compile time.
+///
+/// The path is resolved like with `include_bytes!()`, relative to the
+/// file invoking the macro. The file itself is not embedded.
+///
+/// Large files are slow to hash in const context, about half a minute
+/// per MiB, and rustc warns that constant evaluation is taking a long
+/// time while it runs.
+#[macro_export]
+macro_rules! include_xxh3_64 {
+    ($path:expr) => {{
+        #[allow(long_running_const_eval)]
+        const HASH: u64 = $crate::const_xxh3_64(::core::include_bytes!($path));
+        HASH
+    }};
+}
+
+/// XXH3 128 bit hash of a file, computed at compile time.
+///
+/// The path is resolved like with `include_bytes!()`, relative to the
+/// file invoking the macro. The file itself is not embedded. Like with
+/// include_xxh3_64!(), large files are slow to hash.
+///
+/// Can be used to verify bundled assets at runtime:
+///
+/// ```
+/// const CHECKSUM: u128 = xxhrs::include_xxh3_128!("fixtures/data");
+/// let data = std::fs::read("src/fixtures/data").unwrap();
+/// assert_eq!(xxhrs::XXH3_128::hash(&data), CHECKSUM);
+/// ```
+#[macro_export]
+macro_rules! include_xxh3_128 {
+    ($path:expr) => {{
+        #[allow(long_running_const_eval)]
+        const HASH: u128 = $crate::const_xxh3_128(::core::include_bytes!($path));
+        HASH
+    }};
+}
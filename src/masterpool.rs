@@ -0,0 +1,62 @@
+use crate::{
+    entropy::{EntropyPool, ENTROPY_POOL_SIZE},
+    error::Error,
+    xxh3::XXH3_64,
+};
+use std::{process, sync::Mutex};
+
+/// Process wide random entropy the RandomState* seeds and entropy
+/// pools are derived from.
+///
+/// Asking getrandom for fresh entropy every time a RandomState is created
+/// is comparatively slow, so like std's RandomState we draw random entropy
+/// once and derive the individual instances from it cheaply, by feeding
+/// the master pool together with a counter through the key derivation
+/// function.
+///
+/// The process id is recorded with the pool; after fork() the child
+/// notices the mismatch and draws a master pool of its own instead of
+/// producing the same sequence as the parent.
+struct MasterPool {
+    pid: u32,
+    counter: u64,
+    pool: EntropyPool,
+}
+
+static MASTER: Mutex<Option<MasterPool>> = Mutex::new(None);
+
+/// Produce a unique key made up from a counter and the master pool,
+/// initializing the master pool if necessary.
+fn next_key() -> Result<[u8; ENTROPY_POOL_SIZE + 8], Error> {
+    let pid = process::id();
+
+    // Nothing panics while the lock is held, so poisoning is harmless
+    let mut master = MASTER.lock().unwrap_or_else(|e| e.into_inner());
+    if !matches!(&*master, Some(m) if m.pid == pid) {
+        *master = Some(MasterPool {
+            pid,
+            counter: 0,
+            pool: EntropyPool::try_randomize()?,
+        });
+    }
+
+    let master = master.as_mut().unwrap();
+    master.counter += 1;
+
+    let mut key = [0u8; ENTROPY_POOL_SIZE + 8];
+    key[..8].copy_from_slice(&master.counter.to_le_bytes());
+    key[8..].copy_from_slice(&master.pool.entropy);
+    Ok(key)
+}
+
+/// Derive a fresh random entropy pool from the master pool
+#[inline]
+pub(crate) fn derive_pool() -> Result<EntropyPool, Error> {
+    Ok(EntropyPool::with_key(&next_key()?))
+}
+
+/// Derive a fresh random seed from the master pool
+#[inline]
+pub(crate) fn derive_seed() -> Result<u64, Error> {
+    Ok(XXH3_64::hash(&next_key()?))
+}
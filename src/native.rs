@@ -0,0 +1,765 @@
+// Native rust implementation of the xxhash C API used by xxhrs.
+//
+// With the `pure-rust` feature, this module is used in place of the C
+// library, so the crate can be built without a C compiler or libclang.
+// The items mirror the declarations bindgen generates from xxhash.h:
+// identical names, identical state layouts and identical raw pointer
+// signatures, so the safe wrappers work unchanged with either backend.
+//
+// The hash functions themselves are the const fn port of xxhash.h v0.8.2
+// in consthash.rs; this adds the streaming state handling of the scalar
+// code paths and our XXHRS_* helpers from xxhash_bindings.c. The results
+// are identical to the C implementation.
+
+use crate::consthash::{
+    accumulate, accumulate_512, finalize_long_128, finalize_long_64, init_custom_secret, scramble,
+    xxh32, xxh32_converge, xxh32_finalize, xxh32_init, xxh32_stripe, xxh3_128_short,
+    xxh3_128_with_secret, xxh3_128_with_seed, xxh3_64_short, xxh3_64_with_secret,
+    xxh3_64_with_seed, xxh64, xxh64_converge, xxh64_finalize, xxh64_init, xxh64_stripe, INIT_ACC,
+    K_SECRET, MIDSIZE_MAX, PRIME32_5, PRIME64_5, SECRET_CONSUME_RATE, SECRET_DEFAULT_SIZE,
+    SECRET_LASTACC_START, SECRET_SIZE_MIN, STRIPE_LEN,
+};
+use core::{
+    ffi::{c_int, c_uchar, c_uint, c_void},
+    ptr, slice,
+};
+
+pub type XXH32_hash_t = u32;
+pub type XXH64_hash_t = u64;
+pub type XXH_errorcode = c_uint;
+
+const XXH_OK: XXH_errorcode = 0;
+const XXH_ERROR: XXH_errorcode = 1;
+
+pub const XXH3_SECRET_SIZE_MIN: u32 = SECRET_SIZE_MIN as u32;
+pub const XXH3_SECRET_DEFAULT_SIZE: u32 = SECRET_DEFAULT_SIZE as u32;
+pub const XXH3_INTERNALBUFFER_SIZE: u32 = 256;
+
+const INTERNALBUFFER_SIZE: usize = XXH3_INTERNALBUFFER_SIZE as usize;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XXH32_state_s {
+    pub total_len_32: XXH32_hash_t,
+    pub large_len: XXH32_hash_t,
+    pub v: [XXH32_hash_t; 4],
+    pub mem32: [XXH32_hash_t; 4],
+    pub memsize: XXH32_hash_t,
+    pub reserved: XXH32_hash_t,
+}
+
+pub type XXH32_state_t = XXH32_state_s;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XXH64_state_s {
+    pub total_len: XXH64_hash_t,
+    pub v: [XXH64_hash_t; 4],
+    pub mem64: [XXH64_hash_t; 4],
+    pub memsize: XXH32_hash_t,
+    pub reserved32: XXH32_hash_t,
+    pub reserved64: XXH64_hash_t,
+}
+
+pub type XXH64_state_t = XXH64_state_s;
+
+#[repr(C, align(64))]
+#[derive(Debug, Copy, Clone)]
+pub struct XXH3_state_s {
+    pub acc: [XXH64_hash_t; 8],
+    pub customSecret: [c_uchar; SECRET_DEFAULT_SIZE],
+    pub buffer: [c_uchar; INTERNALBUFFER_SIZE],
+    pub bufferedSize: XXH32_hash_t,
+    pub useSeed: XXH32_hash_t,
+    pub nbStripesSoFar: usize,
+    pub totalLen: XXH64_hash_t,
+    pub nbStripesPerBlock: usize,
+    pub secretLimit: usize,
+    pub seed: XXH64_hash_t,
+    pub reserved64: XXH64_hash_t,
+    pub extSecret: *const c_uchar,
+}
+
+pub type XXH3_state_t = XXH3_state_s;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XXH128_hash_t {
+    pub low64: XXH64_hash_t,
+    pub high64: XXH64_hash_t,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XXH32_canonical_t {
+    pub digest: [c_uchar; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XXH64_canonical_t {
+    pub digest: [c_uchar; 8],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XXH128_canonical_t {
+    pub digest: [c_uchar; 16],
+}
+
+/// View a pointer/length pair as slice; like the C library, this accepts
+/// a null pointer for empty inputs
+#[inline(always)]
+unsafe fn bytes<'a>(ptr: *const c_void, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr as *const u8, len)
+    }
+}
+
+#[inline(always)]
+fn to_xxh128(h: u128) -> XXH128_hash_t {
+    XXH128_hash_t {
+        low64: h as u64,
+        high64: (h >> 64) as u64,
+    }
+}
+
+// XXH32 //
+
+// The version whose output this implementation reproduces
+pub unsafe fn XXH_versionNumber() -> c_uint {
+    802
+}
+
+pub unsafe fn XXH32(input: *const c_void, length: usize, seed: XXH32_hash_t) -> XXH32_hash_t {
+    xxh32(bytes(input, length), seed)
+}
+
+pub unsafe fn XXH32_reset(statePtr: *mut XXH32_state_t, seed: XXH32_hash_t) -> XXH_errorcode {
+    statePtr.write(XXH32_state_t {
+        total_len_32: 0,
+        large_len: 0,
+        v: xxh32_init(seed),
+        mem32: [0; 4],
+        memsize: 0,
+        reserved: 0,
+    });
+    XXH_OK
+}
+
+pub unsafe fn XXH32_update(
+    statePtr: *mut XXH32_state_t,
+    input: *const c_void,
+    length: usize,
+) -> XXH_errorcode {
+    let state = &mut *statePtr;
+    let mut input = bytes(input, length);
+    let mem = &mut *(state.mem32.as_mut_ptr() as *mut [u8; 16]);
+    let memsize = state.memsize as usize;
+
+    state.total_len_32 = state.total_len_32.wrapping_add(input.len() as u32);
+    state.large_len |= (input.len() >= 16 || state.total_len_32 >= 16) as u32;
+
+    if memsize + input.len() < 16 {
+        mem[memsize..memsize + input.len()].copy_from_slice(input);
+        state.memsize += input.len() as u32;
+        return XXH_OK;
+    }
+
+    if memsize != 0 {
+        let (head, tail) = input.split_at(16 - memsize);
+        mem[memsize..].copy_from_slice(head);
+        state.v = xxh32_stripe(state.v, mem, 0);
+        input = tail;
+    }
+
+    let mut stripes = input.chunks_exact(16);
+    for s in &mut stripes {
+        state.v = xxh32_stripe(state.v, s, 0);
+    }
+    let rest = stripes.remainder();
+    mem[..rest.len()].copy_from_slice(rest);
+    state.memsize = rest.len() as u32;
+    XXH_OK
+}
+
+pub unsafe fn XXH32_digest(statePtr: *const XXH32_state_t) -> XXH32_hash_t {
+    let state = &*statePtr;
+    let mem = &*(state.mem32.as_ptr() as *const [u8; 16]);
+    let h = if state.large_len != 0 {
+        xxh32_converge(&state.v)
+    } else {
+        state.v[2].wrapping_add(PRIME32_5)
+    };
+    xxh32_finalize(
+        h.wrapping_add(state.total_len_32),
+        &mem[..state.memsize as usize],
+        0,
+    )
+}
+
+pub unsafe fn XXH32_canonicalFromHash(dst: *mut XXH32_canonical_t, hash: XXH32_hash_t) {
+    (*dst).digest = hash.to_be_bytes();
+}
+
+pub unsafe fn XXH32_hashFromCanonical(src: *const XXH32_canonical_t) -> XXH32_hash_t {
+    u32::from_be_bytes((*src).digest)
+}
+
+// XXH64 //
+
+pub unsafe fn XXH64(input: *const c_void, length: usize, seed: XXH64_hash_t) -> XXH64_hash_t {
+    xxh64(bytes(input, length), seed)
+}
+
+pub unsafe fn XXH64_reset(statePtr: *mut XXH64_state_t, seed: XXH64_hash_t) -> XXH_errorcode {
+    statePtr.write(XXH64_state_t {
+        total_len: 0,
+        v: xxh64_init(seed),
+        mem64: [0; 4],
+        memsize: 0,
+        reserved32: 0,
+        reserved64: 0,
+    });
+    XXH_OK
+}
+
+pub unsafe fn XXH64_update(
+    statePtr: *mut XXH64_state_t,
+    input: *const c_void,
+    length: usize,
+) -> XXH_errorcode {
+    let state = &mut *statePtr;
+    let mut input = bytes(input, length);
+    let mem = &mut *(state.mem64.as_mut_ptr() as *mut [u8; 32]);
+    let memsize = state.memsize as usize;
+
+    state.total_len = state.total_len.wrapping_add(input.len() as u64);
+
+    if memsize + input.len() < 32 {
+        mem[memsize..memsize + input.len()].copy_from_slice(input);
+        state.memsize += input.len() as u32;
+        return XXH_OK;
+    }
+
+    if memsize != 0 {
+        let (head, tail) = input.split_at(32 - memsize);
+        mem[memsize..].copy_from_slice(head);
+        state.v = xxh64_stripe(state.v, mem, 0);
+        input = tail;
+    }
+
+    let mut stripes = input.chunks_exact(32);
+    for s in &mut stripes {
+        state.v = xxh64_stripe(state.v, s, 0);
+    }
+    let rest = stripes.remainder();
+    mem[..rest.len()].copy_from_slice(rest);
+    state.memsize = rest.len() as u32;
+    XXH_OK
+}
+
+pub unsafe fn XXH64_digest(statePtr: *const XXH64_state_t) -> XXH64_hash_t {
+    let state = &*statePtr;
+    let mem = &*(state.mem64.as_ptr() as *const [u8; 32]);
+    let h = if state.total_len >= 32 {
+        xxh64_converge(&state.v)
+    } else {
+        state.v[2].wrapping_add(PRIME64_5)
+    };
+    xxh64_finalize(
+        h.wrapping_add(state.total_len),
+        &mem[..state.memsize as usize],
+        0,
+    )
+}
+
+pub unsafe fn XXH64_canonicalFromHash(dst: *mut XXH64_canonical_t, hash: XXH64_hash_t) {
+    (*dst).digest = hash.to_be_bytes();
+}
+
+pub unsafe fn XXH64_hashFromCanonical(src: *const XXH64_canonical_t) -> XXH64_hash_t {
+    u64::from_be_bytes((*src).digest)
+}
+
+// XXH3 one shot //
+
+pub unsafe fn XXH3_64bits(data: *const c_void, len: usize) -> XXH64_hash_t {
+    xxh3_64_with_seed(bytes(data, len), 0)
+}
+
+pub unsafe fn XXH3_64bits_withSeed(
+    data: *const c_void,
+    len: usize,
+    seed: XXH64_hash_t,
+) -> XXH64_hash_t {
+    xxh3_64_with_seed(bytes(data, len), seed)
+}
+
+pub unsafe fn XXH3_64bits_withSecret(
+    data: *const c_void,
+    len: usize,
+    secret: *const c_void,
+    secretSize: usize,
+) -> XXH64_hash_t {
+    xxh3_64_with_secret(bytes(data, len), bytes(secret, secretSize))
+}
+
+pub unsafe fn XXH3_64bits_withSecretandSeed(
+    data: *const c_void,
+    len: usize,
+    secret: *const c_void,
+    secretSize: usize,
+    seed: XXH64_hash_t,
+) -> XXH64_hash_t {
+    if len <= MIDSIZE_MAX {
+        xxh3_64_short(bytes(data, len), &K_SECRET, seed)
+    } else {
+        xxh3_64_with_secret(bytes(data, len), bytes(secret, secretSize))
+    }
+}
+
+pub unsafe fn XXH3_128bits(data: *const c_void, len: usize) -> XXH128_hash_t {
+    to_xxh128(xxh3_128_with_seed(bytes(data, len), 0))
+}
+
+pub unsafe fn XXH3_128bits_withSeed(
+    data: *const c_void,
+    len: usize,
+    seed: XXH64_hash_t,
+) -> XXH128_hash_t {
+    to_xxh128(xxh3_128_with_seed(bytes(data, len), seed))
+}
+
+pub unsafe fn XXH3_128bits_withSecret(
+    data: *const c_void,
+    len: usize,
+    secret: *const c_void,
+    secretSize: usize,
+) -> XXH128_hash_t {
+    to_xxh128(xxh3_128_with_secret(
+        bytes(data, len),
+        bytes(secret, secretSize),
+    ))
+}
+
+pub unsafe fn XXH3_128bits_withSecretandSeed(
+    input: *const c_void,
+    length: usize,
+    secret: *const c_void,
+    secretSize: usize,
+    seed64: XXH64_hash_t,
+) -> XXH128_hash_t {
+    to_xxh128(if length <= MIDSIZE_MAX {
+        xxh3_128_short(bytes(input, length), &K_SECRET, seed64)
+    } else {
+        xxh3_128_with_secret(bytes(input, length), bytes(secret, secretSize))
+    })
+}
+
+// XXH3 streaming //
+
+/// The secret the state was reset with
+#[inline(always)]
+unsafe fn state_secret(
+    custom_secret: &[u8; SECRET_DEFAULT_SIZE],
+    ext_secret: *const u8,
+    secret_limit: usize,
+) -> &[u8] {
+    if ext_secret.is_null() {
+        &custom_secret[..secret_limit + STRIPE_LEN]
+    } else {
+        slice::from_raw_parts(ext_secret, secret_limit + STRIPE_LEN)
+    }
+}
+
+/// Like XXH3_reset_internal, this leaves the secret and buffer untouched;
+/// the state is accessed through the raw pointer since these may be
+/// uninitialized.
+unsafe fn reset_internal(
+    statePtr: *mut XXH3_state_t,
+    seed: u64,
+    secret: *const c_void,
+    secretSize: usize,
+) {
+    debug_assert!(secretSize >= SECRET_SIZE_MIN);
+    (*statePtr).acc = INIT_ACC;
+    (*statePtr).bufferedSize = 0;
+    (*statePtr).useSeed = (seed != 0) as u32;
+    (*statePtr).nbStripesSoFar = 0;
+    (*statePtr).totalLen = 0;
+    (*statePtr).seed = seed;
+    (*statePtr).extSecret = secret as *const u8;
+    (*statePtr).secretLimit = secretSize - STRIPE_LEN;
+    (*statePtr).nbStripesPerBlock = (secretSize - STRIPE_LEN) / SECRET_CONSUME_RATE;
+}
+
+unsafe fn reset_with_seed(statePtr: *mut XXH3_state_t, seed: u64) {
+    if seed == 0 {
+        reset_internal(
+            statePtr,
+            0,
+            K_SECRET.as_ptr() as *const c_void,
+            SECRET_DEFAULT_SIZE,
+        );
+        return;
+    }
+    if seed != (*statePtr).seed || !(*statePtr).extSecret.is_null() {
+        (*statePtr).customSecret = init_custom_secret(seed);
+    }
+    reset_internal(statePtr, seed, ptr::null(), SECRET_DEFAULT_SIZE);
+}
+
+/// Processes stripes from `input`, scrambling the accumulators after
+/// every full block
+fn consume_stripes(
+    acc: &mut [u64; 8],
+    nb_stripes_so_far: &mut usize,
+    nb_stripes_per_block: usize,
+    mut input: &[u8],
+    mut nb_stripes: usize,
+    secret: &[u8],
+    secret_limit: usize,
+) {
+    let mut initial_secret = *nb_stripes_so_far * SECRET_CONSUME_RATE;
+    if nb_stripes >= nb_stripes_per_block - *nb_stripes_so_far {
+        let mut nb_stripes_this_iter = nb_stripes_per_block - *nb_stripes_so_far;
+        loop {
+            *acc = accumulate(*acc, input, 0, secret, initial_secret, nb_stripes_this_iter);
+            *acc = scramble(*acc, secret, secret_limit);
+            input = &input[nb_stripes_this_iter * STRIPE_LEN..];
+            nb_stripes -= nb_stripes_this_iter;
+            nb_stripes_this_iter = nb_stripes_per_block;
+            initial_secret = 0;
+            if nb_stripes < nb_stripes_per_block {
+                break;
+            }
+        }
+        *nb_stripes_so_far = 0;
+    }
+    if nb_stripes > 0 {
+        *acc = accumulate(*acc, input, 0, secret, initial_secret, nb_stripes);
+        *nb_stripes_so_far += nb_stripes;
+    }
+}
+
+unsafe fn update(state: &mut XXH3_state_t, mut input: &[u8]) {
+    let secret = state_secret(&state.customSecret, state.extSecret, state.secretLimit);
+    let buffered = state.bufferedSize as usize;
+
+    state.totalLen = state.totalLen.wrapping_add(input.len() as u64);
+
+    if input.len() <= INTERNALBUFFER_SIZE - buffered {
+        state.buffer[buffered..buffered + input.len()].copy_from_slice(input);
+        state.bufferedSize += input.len() as u32;
+        return;
+    }
+
+    // Complete the buffer, then consume it
+    if buffered != 0 {
+        let (head, tail) = input.split_at(INTERNALBUFFER_SIZE - buffered);
+        state.buffer[buffered..].copy_from_slice(head);
+        consume_stripes(
+            &mut state.acc,
+            &mut state.nbStripesSoFar,
+            state.nbStripesPerBlock,
+            &state.buffer,
+            INTERNALBUFFER_SIZE / STRIPE_LEN,
+            secret,
+            state.secretLimit,
+        );
+        state.bufferedSize = 0;
+        input = tail;
+    }
+
+    // Consume the input directly, keeping the last stripe for digest
+    if input.len() > INTERNALBUFFER_SIZE {
+        let consumed = (input.len() - 1) / STRIPE_LEN * STRIPE_LEN;
+        consume_stripes(
+            &mut state.acc,
+            &mut state.nbStripesSoFar,
+            state.nbStripesPerBlock,
+            input,
+            consumed / STRIPE_LEN,
+            secret,
+            state.secretLimit,
+        );
+        state.buffer[INTERNALBUFFER_SIZE - STRIPE_LEN..]
+            .copy_from_slice(&input[consumed - STRIPE_LEN..consumed]);
+        input = &input[consumed..];
+    }
+
+    state.buffer[..input.len()].copy_from_slice(input);
+    state.bufferedSize = input.len() as u32;
+}
+
+/// Accumulators for a digest of more than MIDSIZE_MAX bytes; works on a
+/// copy so the state can continue ingesting input afterwards
+fn digest_long(state: &XXH3_state_t, secret: &[u8]) -> [u64; 8] {
+    let mut acc = state.acc;
+    let buffered = state.bufferedSize as usize;
+    let mut last_stripe = [0u8; STRIPE_LEN];
+
+    let last_stripe: &[u8] = if buffered >= STRIPE_LEN {
+        let mut nb_stripes_so_far = state.nbStripesSoFar;
+        consume_stripes(
+            &mut acc,
+            &mut nb_stripes_so_far,
+            state.nbStripesPerBlock,
+            &state.buffer,
+            (buffered - 1) / STRIPE_LEN,
+            secret,
+            state.secretLimit,
+        );
+        &state.buffer[buffered - STRIPE_LEN..buffered]
+    } else {
+        // Part of the last stripe is still in the end of the buffer
+        let catchup = STRIPE_LEN - buffered;
+        last_stripe[..catchup].copy_from_slice(&state.buffer[INTERNALBUFFER_SIZE - catchup..]);
+        last_stripe[catchup..].copy_from_slice(&state.buffer[..buffered]);
+        &last_stripe
+    };
+
+    accumulate_512(
+        acc,
+        last_stripe,
+        0,
+        secret,
+        state.secretLimit - SECRET_LASTACC_START,
+    )
+}
+
+pub unsafe fn XXH3_64bits_reset(statePtr: *mut XXH3_state_t) -> XXH_errorcode {
+    reset_with_seed(statePtr, 0);
+    XXH_OK
+}
+
+pub unsafe fn XXH3_64bits_reset_withSeed(
+    statePtr: *mut XXH3_state_t,
+    seed: XXH64_hash_t,
+) -> XXH_errorcode {
+    reset_with_seed(statePtr, seed);
+    XXH_OK
+}
+
+pub unsafe fn XXH3_64bits_reset_withSecret(
+    statePtr: *mut XXH3_state_t,
+    secret: *const c_void,
+    secretSize: usize,
+) -> XXH_errorcode {
+    if secret.is_null() || secretSize < SECRET_SIZE_MIN {
+        return XXH_ERROR;
+    }
+    reset_internal(statePtr, 0, secret, secretSize);
+    XXH_OK
+}
+
+pub unsafe fn XXH3_64bits_update(
+    statePtr: *mut XXH3_state_t,
+    input: *const c_void,
+    length: usize,
+) -> XXH_errorcode {
+    update(&mut *statePtr, bytes(input, length));
+    XXH_OK
+}
+
+pub unsafe fn XXH3_64bits_digest(statePtr: *const XXH3_state_t) -> XXH64_hash_t {
+    let state = &*statePtr;
+    let secret = state_secret(&state.customSecret, state.extSecret, state.secretLimit);
+    if state.totalLen > MIDSIZE_MAX as u64 {
+        finalize_long_64(&digest_long(state, secret), state.totalLen, secret)
+    } else if state.useSeed != 0 {
+        xxh3_64_with_seed(&state.buffer[..state.totalLen as usize], state.seed)
+    } else {
+        xxh3_64_with_secret(&state.buffer[..state.totalLen as usize], secret)
+    }
+}
+
+pub unsafe fn XXH3_128bits_reset(statePtr: *mut XXH3_state_t) -> XXH_errorcode {
+    XXH3_64bits_reset(statePtr)
+}
+
+pub unsafe fn XXH3_128bits_reset_withSeed(
+    statePtr: *mut XXH3_state_t,
+    seed: XXH64_hash_t,
+) -> XXH_errorcode {
+    XXH3_64bits_reset_withSeed(statePtr, seed)
+}
+
+pub unsafe fn XXH3_128bits_reset_withSecret(
+    statePtr: *mut XXH3_state_t,
+    secret: *const c_void,
+    secretSize: usize,
+) -> XXH_errorcode {
+    XXH3_64bits_reset_withSecret(statePtr, secret, secretSize)
+}
+
+pub unsafe fn XXH3_128bits_update(
+    statePtr: *mut XXH3_state_t,
+    input: *const c_void,
+    length: usize,
+) -> XXH_errorcode {
+    XXH3_64bits_update(statePtr, input, length)
+}
+
+// Like xxhash 0.8.2, this checks seed rather than useSeed for short inputs;
+// XXH3_128::finish() handles entropy pools with seed 0 itself
+pub unsafe fn XXH3_128bits_digest(statePtr: *const XXH3_state_t) -> XXH128_hash_t {
+    let state = &*statePtr;
+    let secret = state_secret(&state.customSecret, state.extSecret, state.secretLimit);
+    to_xxh128(if state.totalLen > MIDSIZE_MAX as u64 {
+        finalize_long_128(&digest_long(state, secret), state.totalLen, secret)
+    } else if state.seed != 0 {
+        xxh3_128_with_seed(&state.buffer[..state.totalLen as usize], state.seed)
+    } else {
+        xxh3_128_with_secret(&state.buffer[..state.totalLen as usize], secret)
+    })
+}
+
+pub unsafe fn XXH128_canonicalFromHash(dst: *mut XXH128_canonical_t, hash: XXH128_hash_t) {
+    (*dst).digest = ((hash.high64 as u128) << 64 | hash.low64 as u128).to_be_bytes();
+}
+
+pub unsafe fn XXH128_hashFromCanonical(src: *const XXH128_canonical_t) -> XXH128_hash_t {
+    to_xxh128(u128::from_be_bytes((*src).digest))
+}
+
+// XXH3 secrets //
+
+// Only CustomEntropyPool uses the upstream key derivation
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+pub unsafe fn XXH3_generateSecret(
+    secretBuffer: *mut c_void,
+    secretSize: usize,
+    customSeed: *const c_void,
+    customSeedSize: usize,
+) -> XXH_errorcode {
+    if secretBuffer.is_null() || secretSize < SECRET_SIZE_MIN {
+        return XXH_ERROR;
+    }
+    let custom_seed = if customSeedSize == 0 {
+        &K_SECRET[..]
+    } else if customSeed.is_null() {
+        return XXH_ERROR;
+    } else {
+        bytes(customSeed, customSeedSize)
+    };
+    let secret = slice::from_raw_parts_mut(secretBuffer as *mut u8, secretSize);
+
+    // Fill the secret with copies of the custom seed
+    for chunk in secret.chunks_mut(custom_seed.len()) {
+        chunk.copy_from_slice(&custom_seed[..chunk.len()]);
+    }
+
+    let combine16 = |dst: &mut [u8], h: u128| {
+        for (d, s) in dst[..16].iter_mut().zip(h.to_le_bytes().iter()) {
+            *d ^= s;
+        }
+    };
+
+    let scrambler = xxh3_128_with_seed(custom_seed, 0);
+    let scrambler_canonical = scrambler.to_be_bytes();
+    for (idx, chunk) in secret.chunks_exact_mut(16).enumerate() {
+        combine16(chunk, xxh3_128_with_seed(&scrambler_canonical, idx as u64));
+    }
+    combine16(&mut secret[secretSize - 16..], scrambler);
+    XXH_OK
+}
+
+pub unsafe fn XXH3_generateSecret_fromSeed(secretBuffer: *mut c_void, seed: XXH64_hash_t) {
+    (secretBuffer as *mut [u8; SECRET_DEFAULT_SIZE]).write(init_custom_secret(seed));
+}
+
+// xxhrs extensions; see xxhash_bindings.c //
+
+pub unsafe fn XXHRS_64bits_reset_withSecretCopy(
+    statePtr: *mut XXH3_state_t,
+    secret: *const c_void,
+    secretSize: usize,
+) {
+    debug_assert!(secretSize >= SECRET_SIZE_MIN);
+    debug_assert!(secretSize <= SECRET_DEFAULT_SIZE);
+    reset_internal(statePtr, 0, secret, secretSize);
+    ptr::copy_nonoverlapping(
+        secret as *const u8,
+        ptr::addr_of_mut!((*statePtr).customSecret) as *mut u8,
+        secretSize,
+    );
+    (*statePtr).extSecret = ptr::null();
+}
+
+pub unsafe fn XXHRS_128bits_reset_withSecretCopy(
+    statePtr: *mut XXH3_state_t,
+    secret: *const c_void,
+    secretSize: usize,
+) {
+    XXHRS_64bits_reset_withSecretCopy(statePtr, secret, secretSize);
+}
+
+pub unsafe fn XXHRS_64bits_reset_withSecretandSeedCopy(
+    statePtr: *mut XXH3_state_t,
+    secret: *const c_void,
+    seed: XXH64_hash_t,
+) {
+    XXHRS_64bits_reset_withSecretCopy(statePtr, secret, SECRET_DEFAULT_SIZE);
+    (*statePtr).seed = seed;
+    (*statePtr).useSeed = 1; // always, even if seed==0
+}
+
+pub unsafe fn XXHRS_128bits_reset_withSecretandSeedCopy(
+    statePtr: *mut XXH3_state_t,
+    secret: *const c_void,
+    seed: XXH64_hash_t,
+) {
+    XXHRS_64bits_reset_withSecretandSeedCopy(statePtr, secret, seed);
+}
+
+pub unsafe fn XXHRS_64bits_reset_keepSecret(statePtr: *mut XXH3_state_t) {
+    let use_seed = (*statePtr).useSeed;
+    reset_internal(
+        statePtr,
+        (*statePtr).seed,
+        (*statePtr).extSecret as *const c_void,
+        (*statePtr).secretLimit + STRIPE_LEN,
+    );
+    (*statePtr).useSeed = use_seed;
+}
+
+pub unsafe fn XXHRS_128bits_reset_keepSecret(statePtr: *mut XXH3_state_t) {
+    XXHRS_64bits_reset_keepSecret(statePtr);
+}
+
+/// XXH_SCALAR; there are no vectorized kernels to dispatch between
+const XXH_SCALAR: c_int = 0;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XXHRS_config_t {
+    pub vector: c_int,
+    pub dispatch: c_int,
+    pub sizeOpt: c_int,
+    pub forceMemoryAccess: c_int,
+}
+
+pub unsafe fn XXHRS_config() -> XXHRS_config_t {
+    XXHRS_config_t {
+        vector: XXH_SCALAR,
+        dispatch: 0,
+        sizeOpt: -1,
+        forceMemoryAccess: -1,
+    }
+}
+
+pub unsafe fn XXHRS_backend() -> c_int {
+    XXH_SCALAR
+}
+
+pub unsafe fn XXHRS_setBackend(backend: c_int) -> XXH_errorcode {
+    if backend == XXH_SCALAR {
+        XXH_OK
+    } else {
+        XXH_ERROR
+    }
+}
@@ -0,0 +1,145 @@
+// Portable serialization of the streaming hasher states, so hashing can
+// be resumed in another process.
+//
+// All values are little endian. Every saved state starts with the header
+//
+//   0  magic, b"XXHS"
+//   4  format version
+//   5  algorithm
+//   6  mode
+//   7  zero
+//   8  seed (u64)
+//  16  total length (u64)
+//
+// followed by the accumulators and the buffered input of the algorithm;
+// see save_state() of the respective hasher. Entropy pools are not saved,
+// XXH3 states just record a fingerprint of the pool in use.
+
+use crate::{error::Error, hasher::Mode};
+
+const MAGIC: &[u8; 4] = b"XXHS";
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 24;
+
+/// Size of the states saved by XXH32::save_state()
+pub const XXH32_SAVED_STATE_SIZE: usize = HEADER_SIZE + 16 + 16 + 4 + 4;
+/// Size of the states saved by XXH64::save_state()
+pub const XXH64_SAVED_STATE_SIZE: usize = HEADER_SIZE + 32 + 32 + 4 + 4;
+/// Size of the states saved by XXH3_64::save_state() and XXH3_128::save_state()
+pub const XXH3_SAVED_STATE_SIZE: usize = HEADER_SIZE + 64 + 256 + 4 + 4 + 8 + 8 + 8;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Algorithm {
+    XXH32 = 0,
+    XXH64 = 1,
+    XXH3_64 = 2,
+    XXH3_128 = 3,
+}
+
+impl Algorithm {
+    fn saved_state_size(self) -> usize {
+        match self {
+            Algorithm::XXH32 => XXH32_SAVED_STATE_SIZE,
+            Algorithm::XXH64 => XXH64_SAVED_STATE_SIZE,
+            Algorithm::XXH3_64 | Algorithm::XXH3_128 => XXH3_SAVED_STATE_SIZE,
+        }
+    }
+}
+
+pub(crate) struct StateWriter<const N: usize> {
+    buf: [u8; N],
+    off: usize,
+}
+
+impl<const N: usize> StateWriter<N> {
+    pub(crate) fn new(algorithm: Algorithm, mode: Mode, total_len: u64) -> Self {
+        let (mode, seed) = match mode {
+            Mode::Unseeded => (0, 0),
+            Mode::Seed(seed) => (1, seed),
+            Mode::Entropy => (2, 0),
+            Mode::EntropyAndSeed(seed) => (3, seed),
+        };
+        debug_assert_eq!(N, algorithm.saved_state_size());
+        let mut r = StateWriter {
+            buf: [0; N],
+            off: 0,
+        };
+        r.bytes(MAGIC);
+        r.bytes(&[VERSION, algorithm as u8, mode, 0]);
+        r.u64(seed);
+        r.u64(total_len);
+        r
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        self.buf[self.off..self.off + bytes.len()].copy_from_slice(bytes);
+        self.off += bytes.len();
+    }
+
+    pub(crate) fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    pub(crate) fn finish(self) -> [u8; N] {
+        debug_assert_eq!(self.off, N);
+        self.buf
+    }
+}
+
+pub(crate) struct StateReader<'a> {
+    buf: &'a [u8],
+    pub(crate) mode: Mode,
+    pub(crate) total_len: u64,
+}
+
+impl<'a> StateReader<'a> {
+    /// Parse the header, making sure the state was saved by a hasher
+    /// for the given algorithm
+    pub(crate) fn new(algorithm: Algorithm, state: &'a [u8]) -> Result<Self, Error> {
+        if state.len() != algorithm.saved_state_size()
+            || &state[..4] != MAGIC
+            || state[4] != VERSION
+            || state[5] != algorithm as u8
+            || state[7] != 0
+        {
+            return Err(Error::InvalidSavedState);
+        }
+        let mut r = StateReader {
+            buf: &state[8..],
+            mode: Mode::Unseeded,
+            total_len: 0,
+        };
+        let seed = r.u64();
+        r.total_len = r.u64();
+        r.mode = match (state[6], seed) {
+            (0, 0) => Mode::Unseeded,
+            (1, seed) => Mode::Seed(seed),
+            (2, 0) => Mode::Entropy,
+            (3, seed) => Mode::EntropyAndSeed(seed),
+            _ => return Err(Error::InvalidSavedState),
+        };
+        Ok(r)
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let (r, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        r
+    }
+
+    pub(crate) fn u32(&mut self) -> u32 {
+        let mut r = [0; 4];
+        r.copy_from_slice(self.bytes(4));
+        u32::from_le_bytes(r)
+    }
+
+    pub(crate) fn u64(&mut self) -> u64 {
+        let mut r = [0; 8];
+        r.copy_from_slice(self.bytes(8));
+        u64::from_le_bytes(r)
+    }
+}
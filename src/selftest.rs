@@ -0,0 +1,254 @@
+use crate::{
+    entropy::{EntropyPool, ENTROPY_POOL_SIZE},
+    error::Error,
+    hasher::StreamingHasher,
+    xxh3::{XXH3_128, XXH3_64},
+    xxhash::{XXH32, XXH64},
+};
+use core::convert::TryFrom;
+
+#[cfg(feature = "alloc")]
+use crate::entropy::{CustomEntropyPool, ENTROPY_POOL_SIZE_MIN};
+
+// Known answer tests, using the pseudo random input and seeds of the
+// sanity checks in xxhash's own test suite. The expected hashes were
+// produced by xxhash v0.8.2.
+
+const PRIME32: u32 = 0x9E3779B1;
+const PRIME64: u64 = 0x9E3779B185EBCA8D;
+
+const SANITY_BUFFER_SIZE: usize = 2367;
+
+/// Where the entropy pools are taken from the input
+const SECRET_OFFSET: usize = 7;
+
+static SANITY_BUFFER: [u8; SANITY_BUFFER_SIZE] = sanity_buffer();
+
+const fn sanity_buffer() -> [u8; SANITY_BUFFER_SIZE] {
+    let mut buf = [0u8; SANITY_BUFFER_SIZE];
+    let mut byte_gen = PRIME32 as u64;
+    let mut idx = 0;
+    while idx < SANITY_BUFFER_SIZE {
+        buf[idx] = (byte_gen >> 56) as u8;
+        byte_gen = byte_gen.wrapping_mul(PRIME64);
+        idx += 1;
+    }
+    buf
+}
+
+/// (input length, [XXH32, XXH32 with seed], [XXH64, XXH64 with seed])
+#[rustfmt::skip]
+const XXHASH_VECTORS: &[(usize, [u32; 2], [u64; 2])] = &[
+    (0, [0x02CC5D05, 0x36B78AE7], [0xEF46DB3751D8E999, 0x0B303D920EC349DF]),
+    (1, [0xCF65B03E, 0xB4545AA4], [0xE934A84ADB052768, 0x9C6678669FCD2E6D]),
+    (6, [0x659F0C97, 0x0BCF25C5], [0xC72565B7154268A8, 0xD0907B98E7303B54]),
+    (12, [0xE89B5F9B, 0x05A6C4B5], [0x0723BF50086EAD9A, 0x0F2C37856FC19ED9]),
+    (24, [0xA6276FF0, 0x7AD49212], [0xF75A6DEA42DC5BF4, 0x424347E32F6B7C2E]),
+    (48, [0xBFD05CBD, 0x0ECCC06E], [0xFD0FEEAC7A939933, 0xEBE2B7170799A833]),
+    (80, [0xB8D7E581, 0x65D85230], [0x99BD5D25EB211099, 0xC23B5CE495DE05BE]),
+    (195, [0x70536B96, 0x5637D2B9], [0x52B73ECDB3EF30E4, 0x8A43A760B705416B]),
+    (240, [0xFA6B6557, 0x55DF41D9], [0xB81838D483BAEE53, 0x7C3C8490FE0C1B94]),
+    (241, [0xE5F7C54D, 0x13B52081], [0x95D76C8B4D8FC4D6, 0x6BD0DB4EF4123409]),
+    (403, [0x6675FF5A, 0xBDE7AAB8], [0xD99858FEE82283DF, 0x1B7A5FEA83596B9C]),
+    (512, [0xD485C30A, 0x6CCF94A9], [0x4358D2FDD62B58A7, 0x6E1A3C5263D2DEF2]),
+    (1024, [0xC08E0A35, 0x1D62EA25], [0x4775BF7CACE4D177, 0xCFBC5E785FF33CCD]),
+    (2048, [0x7C535464, 0x89688D5E], [0x5940F2752BC04387, 0x896B632400E68878]),
+    (2367, [0x4C8A9773, 0x6D5366F6], [0xA82418DDEC0EA581, 0x363B532C35E01E25]),
+];
+
+/// (input length, [unseeded, with seed, with entropy pool,
+/// with entropy pool and seed, with custom entropy pool])
+#[rustfmt::skip]
+const XXH3_64_VECTORS: &[(usize, [u64; 5])] = &[
+    (0, [0x2D06800538D394C2, 0xA8A6B918B2F0364A, 0x3559D64878C5C66C, 0xA8A6B918B2F0364A, 0x3559D64878C5C66C]),
+    (1, [0xC44BDFF4074EECDB, 0x032BE332DD766EF8, 0x8A52451418B2DA4D, 0x032BE332DD766EF8, 0x8A52451418B2DA4D]),
+    (6, [0x27B56A84CD2D7325, 0x84589C116AB59AB9, 0x82C90AB0519369AD, 0x84589C116AB59AB9, 0x82C90AB0519369AD]),
+    (12, [0xA713DAF0DFBB77E7, 0xE7303E1B2336DE0E, 0x14631E773B78EC57, 0xE7303E1B2336DE0E, 0x14631E773B78EC57]),
+    (24, [0xA3FE70BF9D3510EB, 0x850E80FC35BDD690, 0xCDD5542E4A9D9FE8, 0x850E80FC35BDD690, 0xCDD5542E4A9D9FE8]),
+    (48, [0x397DA259ECBA1F11, 0xADC2CBAA44ACC616, 0x33ABD54D094B2534, 0xADC2CBAA44ACC616, 0x33ABD54D094B2534]),
+    (80, [0xBCDEFBBB2C47C90A, 0xC6DD0CB699532E73, 0xE687BA1684965297, 0xC6DD0CB699532E73, 0xE687BA1684965297]),
+    (195, [0xCD94217EE362EC3A, 0xBA68003D370CB3D9, 0xA057273F5EECFB20, 0xBA68003D370CB3D9, 0xA057273F5EECFB20]),
+    (240, [0x81C3C2B67F568CCF, 0xCC0F58C27EF3D8EE, 0xDD1F0FAF1A8164C0, 0xCC0F58C27EF3D8EE, 0xDD1F0FAF1A8164C0]),
+    (241, [0xC5A639ECD2030E5E, 0xDDA9B0A161D4829A, 0xE8DFDD45E8B413CB, 0xE8DFDD45E8B413CB, 0x454805371DF98A91]),
+    (403, [0xCDEB804D65C6DEA4, 0x6259F6ECFD6443FD, 0x3F2FACAFA0005E27, 0x3F2FACAFA0005E27, 0x14546019124D43B8]),
+    (512, [0x617E49599013CB6B, 0x3CE457DE14C27708, 0x79C95595B0FC3C5D, 0x79C95595B0FC3C5D, 0x7564693DD526E28D]),
+    (1024, [0xDD85C9B5C1109C5C, 0xEF368A8A2EBABAEF, 0x1F571CEEC8C15259, 0x1F571CEEC8C15259, 0x3538A2D1EA7410D0]),
+    (2048, [0xDD59E2C3A5F038E0, 0x66F81670669ABABC, 0x87CE0EA7C2FFC867, 0x87CE0EA7C2FFC867, 0xD32E975821D6519F]),
+    (2367, [0xCB37AEB9E5D361ED, 0xD2DB3415B942B42A, 0x35312655412CF5A4, 0x35312655412CF5A4, 0x293FA8E5173BB5E7]),
+];
+
+/// Same layout as XXH3_64_VECTORS
+#[rustfmt::skip]
+const XXH3_128_VECTORS: &[(usize, [u128; 5])] = &[
+    (0, [0x99AA06D3014798D86001C324468D497F, 0x00FEAA732A3CE25EA986DFC5D7605BFE, 0x5F70F4EA232F1D38005923CCEECBE8AE, 0x00FEAA732A3CE25EA986DFC5D7605BFE, 0x5F70F4EA232F1D38005923CCEECBE8AE]),
+    (1, [0xA6CD5E9392000F6AC44BDFF4074EECDB, 0x20E49ABCC53B3842032BE332DD766EF8, 0x3A66AF5A9819198E8A52451418B2DA4D, 0x20E49ABCC53B3842032BE332DD766EF8, 0x3A66AF5A9819198E8A52451418B2DA4D]),
+    (6, [0x082AFE0B8162D12A3E7039BDDA43CFC6, 0x014BD95A51CA5DDBC5B54D56038E4E40, 0x376BD91B6432F36D0B61C8ACA7D4778F, 0x014BD95A51CA5DDBC5B54D56038E4E40, 0x376BD91B6432F36D0B61C8ACA7D4778F]),
+    (12, [0x6E3EFD8FC7802B18061A192713F69AD9, 0xFF0D60ACD02ED4015D92B5D7190B12D1, 0x90A3C2D839F57D0FAF82F6EBA263D7D8, 0xFF0D60ACD02ED4015D92B5D7190B12D1, 0x90A3C2D839F57D0FAF82F6EBA263D7D8]),
+    (24, [0x0CE966E4678D37611E7044D28B1B901D, 0xD7895DED1F62559DC6CBF92A70680B19, 0x3476C01AB8B8E821D9ED8351E0BB5526, 0xD7895DED1F62559DC6CBF92A70680B19, 0x3476C01AB8B8E821D9ED8351E0BB5526]),
+    (48, [0xA002AC4E5478227EF942219AED80F67B, 0xBC689F4C0152FB443A94D91333ED395A, 0x2C599633A4D78138D3488D14A0FC9147, 0xBC689F4C0152FB443A94D91333ED395A, 0x2C599633A4D78138D3488D14A0FC9147]),
+    (80, [0xFDF2CEFDE9EAAC8A454AE6BF7A8A532D, 0x19BF02D69BC56833A5EAC764D1FF1166, 0x5DE1C8EB7BD056B812568D8D732F8544, 0x19BF02D69BC56833A5EAC764D1FF1166, 0x5DE1C8EB7BD056B812568D8D732F8544]),
+    (195, [0x7729543A26B207EE3FB593C086A66075, 0x0326104C4D4849E7CF9D9EC2C8C9913F, 0x18783075F43015BE002CDB4476B9A36F, 0x0326104C4D4849E7CF9D9EC2C8C9913F, 0x18783075F43015BE002CDB4476B9A36F]),
+    (240, [0xAA4202DAA2769DC85C9AAE94C8EBE5A0, 0x29D2133D6EA58C5B604E98DB085C1864, 0x8033FD83D4336CA929DD17317E40CBA2, 0x29D2133D6EA58C5B604E98DB085C1864, 0x8033FD83D4336CA929DD17317E40CBA2]),
+    (241, [0x99A80ECF0ECFC647C5A639ECD2030E5E, 0xEC64AFAE6A137582DDA9B0A161D4829A, 0x948E1BA64B2A98D7E8DFDD45E8B413CB, 0x948E1BA64B2A98D7E8DFDD45E8B413CB, 0x0ECDE988107F17F2454805371DF98A91]),
+    (403, [0x1B6DE21E332DD73DCDEB804D65C6DEA4, 0xBED311971E0BE8F26259F6ECFD6443FD, 0x73FC48FF611CEED73F2FACAFA0005E27, 0x73FC48FF611CEED73F2FACAFA0005E27, 0xE14EEDF084A487F314546019124D43B8]),
+    (512, [0x18D2D110DCC9BCA1617E49599013CB6B, 0x925D06B8EC5B80403CE457DE14C27708, 0x86F7A96B5407AA4A79C95595B0FC3C5D, 0x86F7A96B5407AA4A79C95595B0FC3C5D, 0x918C0F2C7656AB6D7564693DD526E28D]),
+    (1024, [0x0D30D24071C64C57DD85C9B5C1109C5C, 0x17600EFE2B493A18EF368A8A2EBABAEF, 0x1530A0499618B1D91F571CEEC8C15259, 0x1530A0499618B1D91F571CEEC8C15259, 0x7663338D0B32666D3538A2D1EA7410D0]),
+    (2048, [0xF736557FD47073A5DD59E2C3A5F038E0, 0x23CC3A2E75EBAAEA66F81670669ABABC, 0xC1F5A9BCCF606F6987CE0EA7C2FFC867, 0xC1F5A9BCCF606F6987CE0EA7C2FFC867, 0xE862D841C07049AFD32E975821D6519F]),
+    (2367, [0xE89C0F6FF369B427CB37AEB9E5D361ED, 0xCCB7A94CCA1A6496D2DB3415B942B42A, 0x7BDA462C7C624F8B35312655412CF5A4, 0x7BDA462C7C624F8B35312655412CF5A4, 0x343654A35ACF0DAE293FA8E5173BB5E7]),
+];
+
+/// XXH3_64 of the whole input with the entropy pool derived from this key
+const KEY: &[u8] = b"xxhrs self test";
+const XXH3_64_KEYED: u64 = 0x898F8C004CD4DE17;
+
+fn streamed<H: StreamingHasher>(mut hasher: H, input: &[u8]) -> H::Output {
+    let (a, b) = input.split_at(input.len() / 3);
+    hasher.write(a);
+    hasher.write(b);
+    hasher.finish()
+}
+
+/// Compare both the one shot hash and the streaming hasher to the expected hash
+fn check<H>(
+    what: &'static str,
+    input: &[u8],
+    hash: H::Output,
+    hasher: H,
+    expected: H::Output,
+) -> Result<(), Error>
+where
+    H: StreamingHasher,
+    H::Output: PartialEq,
+{
+    if hash != expected || streamed(hasher, input) != expected {
+        return Err(Error::SelfTest(what, input.len()));
+    }
+    Ok(())
+}
+
+/// Check all hash functions against known answers.
+///
+/// Every hash function is tested unseeded, with a seed and, for XXH3, with
+/// entropy pools, on inputs of various lengths; both in one go and
+/// streaming. Fails with `Error::SelfTest` if any hash differs from the one
+/// xxhash v0.8.2 produces, so a program persisting hashes can refuse to run
+/// against a library that would produce different ones.
+///
+/// ```
+/// xxhrs::self_test().expect("xxhash produces unexpected hashes");
+/// ```
+pub fn self_test() -> Result<(), Error> {
+    let secret = &SANITY_BUFFER[SECRET_OFFSET..];
+    let pool = EntropyPool::try_from(&secret[..ENTROPY_POOL_SIZE])?;
+    #[cfg(feature = "alloc")]
+    let custom = CustomEntropyPool::try_from(&secret[..ENTROPY_POOL_SIZE_MIN + 11])?;
+
+    for &(len, xxh32, xxh64) in XXHASH_VECTORS {
+        let input = &SANITY_BUFFER[..len];
+        check("XXH32", input, XXH32::hash(input), XXH32::new(), xxh32[0])?;
+        check(
+            "XXH32 with seed",
+            input,
+            XXH32::hash_with_seed(PRIME32, input),
+            XXH32::with_seed(PRIME32),
+            xxh32[1],
+        )?;
+        check("XXH64", input, XXH64::hash(input), XXH64::new(), xxh64[0])?;
+        check(
+            "XXH64 with seed",
+            input,
+            XXH64::hash_with_seed(PRIME64, input),
+            XXH64::with_seed(PRIME64),
+            xxh64[1],
+        )?;
+    }
+
+    for &(len, xxh3) in XXH3_64_VECTORS {
+        let input = &SANITY_BUFFER[..len];
+        check(
+            "XXH3_64",
+            input,
+            XXH3_64::hash(input),
+            XXH3_64::new(),
+            xxh3[0],
+        )?;
+        check(
+            "XXH3_64 with seed",
+            input,
+            XXH3_64::hash_with_seed(PRIME64, input),
+            XXH3_64::with_seed(PRIME64),
+            xxh3[1],
+        )?;
+        check(
+            "XXH3_64 with entropy",
+            input,
+            XXH3_64::hash_with_entropy(&pool, input),
+            XXH3_64::with_entropy(&pool),
+            xxh3[2],
+        )?;
+        check(
+            "XXH3_64 with entropy and seed",
+            input,
+            XXH3_64::hash_with_entropy_and_seed(&pool, PRIME64, input),
+            XXH3_64::with_entropy_and_seed(&pool, PRIME64),
+            xxh3[3],
+        )?;
+        #[cfg(feature = "alloc")]
+        check(
+            "XXH3_64 with custom entropy",
+            input,
+            XXH3_64::hash_with_custom_entropy(&custom, input),
+            XXH3_64::with_custom_entropy(&custom),
+            xxh3[4],
+        )?;
+    }
+
+    for &(len, xxh3) in XXH3_128_VECTORS {
+        let input = &SANITY_BUFFER[..len];
+        check(
+            "XXH3_128",
+            input,
+            XXH3_128::hash(input),
+            XXH3_128::new(),
+            xxh3[0],
+        )?;
+        check(
+            "XXH3_128 with seed",
+            input,
+            XXH3_128::hash_with_seed(PRIME64, input),
+            XXH3_128::with_seed(PRIME64),
+            xxh3[1],
+        )?;
+        check(
+            "XXH3_128 with entropy",
+            input,
+            XXH3_128::hash_with_entropy(&pool, input),
+            XXH3_128::with_entropy(&pool),
+            xxh3[2],
+        )?;
+        check(
+            "XXH3_128 with entropy and seed",
+            input,
+            XXH3_128::hash_with_entropy_and_seed(&pool, PRIME64, input),
+            XXH3_128::with_entropy_and_seed(&pool, PRIME64),
+            xxh3[3],
+        )?;
+        #[cfg(feature = "alloc")]
+        check(
+            "XXH3_128 with custom entropy",
+            input,
+            XXH3_128::hash_with_custom_entropy(&custom, input),
+            XXH3_128::with_custom_entropy(&custom),
+            xxh3[4],
+        )?;
+    }
+
+    let key = EntropyPool::with_key(KEY);
+    check(
+        "XXH3_64 with key",
+        &SANITY_BUFFER,
+        XXH3_64::hash_with_entropy(&key, &SANITY_BUFFER),
+        XXH3_64::with_entropy(&key),
+        XXH3_64_KEYED,
+    )
+}
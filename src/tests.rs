@@ -1,5 +1,16 @@
 use crate::{
-    entropy::{EntropyPool, ENTROPY_POOL_SIZE},
+    backend::{active_backend, set_backend, Backend},
+    buildinfo::{build_info, xxhash_version},
+    consthash::{
+        const_xxh32, const_xxh32_with_seed, const_xxh3_128, const_xxh3_128_with_seed,
+        const_xxh3_64, const_xxh3_64_with_seed, const_xxh64, const_xxh64_with_seed,
+    },
+    digest::{Hash128, Hash32, Hash64},
+    entropy::{EntropyPool, ENTROPY_POOL_SIZE_MIN},
+    error::Error,
+    fixedstate::{FixedStateXXH32, FixedStateXXH3_128, FixedStateXXH3_64, FixedStateXXH64},
+    hasher::{Mode, StreamingHasher},
+    selftest::self_test,
     xxh3::{XXH3_128, XXH3_64},
     xxhash::{XXH32, XXH64},
 };
@@ -7,9 +18,20 @@ use std::collections::{HashMap, HashSet};
 use std::hash::{BuildHasher, Hasher};
 use std::{cmp::min, default::Default};
 
-#[cfg(feature = "random_entropy")]
-use crate::buildhash::{
-    RandomStateXXH32, RandomStateXXH3_128, RandomStateXXH3_64, RandomStateXXH64,
+#[cfg(feature = "alloc")]
+use crate::entropy::CustomEntropyPool;
+#[cfg(any(feature = "alloc", not(feature = "pure-rust")))]
+use crate::entropy::ENTROPY_POOL_SIZE;
+#[cfg(feature = "alloc")]
+use std::convert::TryFrom;
+
+#[cfg(all(feature = "random_entropy", feature = "std"))]
+use crate::{
+    buildhash::{
+        RandomStateXXH32, RandomStateXXH3_128, RandomStateXXH3_128Seeded, RandomStateXXH3_64,
+        RandomStateXXH3_64Seeded, RandomStateXXH64,
+    },
+    masterpool::{derive_pool, derive_seed},
 };
 
 const SEED32: u32 = 0xf7649871;
@@ -24,9 +46,17 @@ const XXH3_64_KEYED: u64 = 0x8ed31a1c680cec2a;
 const XXH3_128_HASH: u128 = 0x085fd9804f34051d8a24edfe37edf1ea;
 const XXH3_128_SEEDED: u128 = 0x9c43c2c76f8b3de0bf15a1f1e41d08ae;
 const XXH3_128_KEYED: u128 = 0xdf2cabdf86a50f6e8ed31a1c680cec2a;
+const XXH3_64_KEYED_SEEDED: u64 = 0x8ed31a1c680cec2a;
+const XXH3_128_KEYED_SEEDED: u128 = 0xdf2cabdf86a50f6e8ed31a1c680cec2a;
+const XXH3_64_KEYED_SEEDED_SHORT: u64 = 0xccc7a598d2562561;
+const XXH3_128_KEYED_SEEDED_SHORT: u128 = 0xd897f71377f6e761dfb80151a457be21;
+const XXH3_64_KEYED_SEED0_SHORT: u64 = 0x12e50d2904a8fcf6;
+const XXH3_128_KEYED_SEED0_SHORT: u128 = 0xd76d716dbb89f018246a786c01c475c4;
 
 const SECRET: &[u8] = include_bytes!("fixtures/secret");
 const DATA: &[u8] = include_bytes!("fixtures/data");
+// Inputs of up to 240 bytes take a different code path in XXH3
+const SHORT: usize = 100;
 
 static SEED64_ENTROPY: EntropyPool = EntropyPool {
     entropy: *include_bytes!("fixtures/seed64_entropy"),
@@ -34,12 +64,159 @@ static SEED64_ENTROPY: EntropyPool = EntropyPool {
 const SECRET_ENTROPY: EntropyPool = EntropyPool {
     entropy: *include_bytes!("fixtures/secret_entropy"),
 };
+#[cfg(feature = "alloc")]
+const CUSTOM_SECRET_ENTROPY: &[u8] = include_bytes!("fixtures/custom_secret_entropy");
 
 const ENTROPY_POOL_42_DEBUG: &str = include_str!("fixtures/entropy_pool_42_debug");
 
 #[test]
 fn test_entropy_derivation() {
     assert_eq!(EntropyPool::with_key(SECRET), SECRET_ENTROPY);
+    assert_eq!(EntropyPool::from_seed(SEED64), SEED64_ENTROPY);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_custom_entropy_pool() {
+    assert_eq!(
+        CustomEntropyPool::new(vec![0u8; ENTROPY_POOL_SIZE_MIN - 1]),
+        Err(Error::EntropyPoolSize(ENTROPY_POOL_SIZE_MIN - 1))
+    );
+    assert_eq!(
+        CustomEntropyPool::with_key(0, SECRET),
+        Err(Error::EntropyPoolSize(0))
+    );
+    assert_eq!(
+        CustomEntropyPool::with_key(ENTROPY_POOL_SIZE, SECRET)
+            .unwrap()
+            .as_bytes(),
+        CUSTOM_SECRET_ENTROPY
+    );
+    assert_eq!(
+        CustomEntropyPool::from(SECRET_ENTROPY.clone()).as_bytes(),
+        &SECRET_ENTROPY.entropy[..]
+    );
+    assert_eq!(
+        CustomEntropyPool::try_from(SECRET).unwrap().as_bytes(),
+        SECRET
+    );
+    assert_eq!(
+        CustomEntropyPool::try_from(SECRET.to_vec())
+            .unwrap()
+            .as_bytes(),
+        SECRET
+    );
+    assert_eq!(
+        CustomEntropyPool::try_from(&SECRET[..10]),
+        Err(Error::EntropyPoolSize(10))
+    );
+
+    assert_eq!(
+        EntropyPool::try_from(&SECRET_ENTROPY.entropy[..]),
+        Ok(SECRET_ENTROPY)
+    );
+    assert_eq!(
+        EntropyPool::try_from(&SECRET[..ENTROPY_POOL_SIZE_MIN]),
+        Err(Error::EntropyPoolSize(ENTROPY_POOL_SIZE_MIN))
+    );
+    assert_eq!(
+        EntropyPool::try_from(SECRET),
+        Err(Error::EntropyPoolSize(SECRET.len()))
+    );
+
+    let pool = CustomEntropyPool::from(SECRET_ENTROPY.clone());
+    assert_eq!(
+        XXH3_64::hash_with_custom_entropy(&pool, DATA),
+        XXH3_64_KEYED
+    );
+    assert_eq!(
+        XXH3_128::hash_with_custom_entropy(&pool, DATA),
+        XXH3_128_KEYED
+    );
+
+    for size in [ENTROPY_POOL_SIZE_MIN, 150, ENTROPY_POOL_SIZE, 193, 256]
+        .iter()
+        .copied()
+    {
+        let pool = CustomEntropyPool::new(SECRET[..size].to_vec()).unwrap();
+        for len in [0, 1, 100, 240, 241, 1024, DATA.len()].iter().copied() {
+            let d = &DATA[..len];
+            let (out64, out128) = unsafe {
+                (
+                    XXH3_64::hash_with_entropy_buffer(&SECRET[..size], d),
+                    XXH3_128::hash_with_entropy_buffer(&SECRET[..size], d),
+                )
+            };
+            assert_eq!(XXH3_64::hash_with_custom_entropy(&pool, d), out64);
+            assert_eq!(XXH3_128::hash_with_custom_entropy(&pool, d), out128);
+
+            let mut h64 = XXH3_64::with_custom_entropy(&pool);
+            let mut h128 = XXH3_128::with_custom_entropy(&pool);
+            h64.write(&d[..len / 2]);
+            h128.write(&d[..len / 2]);
+
+            // Cloned hashers must not depend on the original
+            let (mut c64, mut c128) = (h64.clone(), h128.clone());
+            drop((h64, h128));
+            c64.write(&d[len / 2..]);
+            c128.write(&d[len / 2..]);
+            assert_eq!(c64.finish(), out64);
+            assert_eq!(c128.finish(), out128);
+
+            StreamingHasher::reset(&mut c64);
+            StreamingHasher::reset(&mut c128);
+            c64.write(d);
+            c128.write(d);
+            assert_eq!(c64.finish(), out64);
+            assert_eq!(c128.finish(), out128);
+        }
+    }
+}
+
+#[test]
+fn test_entropy_from_seed() {
+    for seed in [0, 1, SEED64, u64::MAX].iter().copied() {
+        let pool = EntropyPool::from_seed(seed);
+        for len in [
+            0,
+            1,
+            3,
+            4,
+            8,
+            9,
+            16,
+            17,
+            128,
+            129,
+            240,
+            241,
+            1024,
+            DATA.len(),
+        ]
+        .iter()
+        .copied()
+        {
+            let d = &DATA[..len];
+            assert_eq!(
+                XXH3_64::hash_with_entropy_and_seed(&pool, seed, d),
+                XXH3_64::hash_with_seed(seed, d)
+            );
+            assert_eq!(
+                XXH3_128::hash_with_entropy_and_seed(&pool, seed, d),
+                XXH3_128::hash_with_seed(seed, d)
+            );
+            if len > 240 {
+                assert_eq!(
+                    XXH3_64::hash_with_entropy(&pool, d),
+                    XXH3_64::hash_with_seed(seed, d)
+                );
+                assert_eq!(
+                    XXH3_128::hash_with_entropy(&pool, d),
+                    XXH3_128::hash_with_seed(seed, d)
+                );
+            }
+        }
+    }
 }
 
 #[test]
@@ -66,6 +243,32 @@ fn test_one_shot() {
         XXH3_128::hash_with_entropy(&SECRET_ENTROPY, DATA),
         XXH3_128_KEYED
     );
+    assert_eq!(
+        XXH3_64::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, DATA),
+        XXH3_64_KEYED_SEEDED
+    );
+    assert_eq!(
+        XXH3_64::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, &DATA[..SHORT]),
+        XXH3_64_KEYED_SEEDED_SHORT
+    );
+    assert_eq!(
+        XXH3_128::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, DATA),
+        XXH3_128_KEYED_SEEDED
+    );
+    assert_eq!(
+        XXH3_128::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, &DATA[..SHORT]),
+        XXH3_128_KEYED_SEEDED_SHORT
+    );
+
+    // Short inputs just use the seed
+    assert_eq!(
+        XXH3_64::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, &DATA[..SHORT]),
+        XXH3_64::hash_with_seed(SEED64, &DATA[..SHORT])
+    );
+    assert_eq!(
+        XXH3_128::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, &DATA[..SHORT]),
+        XXH3_128::hash_with_seed(SEED64, &DATA[..SHORT])
+    );
 
     unsafe {
         assert_eq!(
@@ -87,6 +290,557 @@ fn test_one_shot() {
     }
 }
 
+#[test]
+fn test_const_hash() {
+    const H32: u32 = const_xxh32(DATA);
+    const H32_SEEDED: u32 = const_xxh32_with_seed(SEED32, DATA);
+    const H64: u64 = const_xxh64(DATA);
+    const H64_SEEDED: u64 = const_xxh64_with_seed(SEED64, DATA);
+    const H3_64: u64 = const_xxh3_64(DATA);
+    const H3_64_SEEDED: u64 = const_xxh3_64_with_seed(SEED64, DATA);
+    const H3_128: u128 = const_xxh3_128(DATA);
+    const H3_128_SEEDED: u128 = const_xxh3_128_with_seed(SEED64, DATA);
+
+    assert_eq!(H32, XXH32_HASH);
+    assert_eq!(H32_SEEDED, XXH32_SEEDED);
+    assert_eq!(H64, XXH64_HASH);
+    assert_eq!(H64_SEEDED, XXH64_SEEDED);
+    assert_eq!(H3_64, XXH3_64_HASH);
+    assert_eq!(H3_64_SEEDED, XXH3_64_SEEDED);
+    assert_eq!(H3_128, XXH3_128_HASH);
+    assert_eq!(H3_128_SEEDED, XXH3_128_SEEDED);
+
+    // Every length up to a few blocks covers all short paths and the
+    // partial block/last stripe handling of long inputs
+    for len in 0..=2200 {
+        let d = &DATA[..len];
+        assert_eq!(const_xxh32(d), XXH32::hash(d));
+        assert_eq!(
+            const_xxh32_with_seed(SEED32, d),
+            XXH32::hash_with_seed(SEED32, d)
+        );
+        assert_eq!(const_xxh64(d), XXH64::hash(d));
+        assert_eq!(
+            const_xxh64_with_seed(SEED64, d),
+            XXH64::hash_with_seed(SEED64, d)
+        );
+        assert_eq!(const_xxh3_64(d), XXH3_64::hash(d));
+        assert_eq!(
+            const_xxh3_64_with_seed(SEED64, d),
+            XXH3_64::hash_with_seed(SEED64, d)
+        );
+        assert_eq!(const_xxh3_128(d), XXH3_128::hash(d));
+        assert_eq!(
+            const_xxh3_128_with_seed(SEED64, d),
+            XXH3_128::hash_with_seed(SEED64, d)
+        );
+    }
+}
+
+#[test]
+fn test_hash_macros() {
+    use crate::{include_xxh3_128, include_xxh3_64, xxh3_128, xxh3_64};
+
+    const TAG: u64 = xxh3_64!("MyData");
+    assert_eq!(TAG, XXH3_64::hash(b"MyData"));
+    assert_eq!(xxh3_64!(b"MyData"), XXH3_64::hash(b"MyData"));
+    assert_eq!(xxh3_64!(DATA), XXH3_64_HASH);
+    assert_eq!(xxh3_128!("MyData"), XXH3_128::hash(b"MyData"));
+    assert_eq!(xxh3_128!(b""), XXH3_128::hash(b""));
+    assert_eq!(xxh3_128!(DATA), XXH3_128_HASH);
+    assert_eq!(include_xxh3_64!("fixtures/data"), XXH3_64_HASH);
+    assert_eq!(include_xxh3_128!("fixtures/data"), XXH3_128_HASH);
+
+    // Exceeds the step limit of const evaluation
+    const LARGE: &[u8] = include_bytes!("fixtures/data_1m");
+    assert_eq!(include_xxh3_64!("fixtures/data_1m"), XXH3_64::hash(LARGE));
+    assert_eq!(include_xxh3_128!("fixtures/data_1m"), XXH3_128::hash(LARGE));
+}
+
+#[test]
+fn test_backends() {
+    let active = active_backend();
+    if active == Backend::Unknown {
+        // The system libxxhash, which uses a fixed kernel
+        assert!(build_info().system_xxhash);
+        assert_eq!(
+            set_backend(Backend::Scalar),
+            Err(Error::UnsupportedBackend(Backend::Scalar))
+        );
+        return;
+    }
+    assert_eq!(set_backend(active), Ok(()));
+
+    // All kernels must produce the same hashes; other tests running
+    // concurrently are unaffected by switching between them
+    let mut tested = 0;
+    for backend in [
+        Backend::Scalar,
+        Backend::Sse2,
+        Backend::Avx2,
+        Backend::Avx512,
+    ]
+    .iter()
+    .copied()
+    {
+        match set_backend(backend) {
+            Ok(()) => tested += 1,
+            Err(e) => {
+                assert_eq!(e, Error::UnsupportedBackend(backend));
+                continue;
+            }
+        }
+        assert_eq!(active_backend(), backend);
+        assert_eq!(self_test(), Ok(()));
+
+        assert_eq!(XXH3_64::hash(DATA), XXH3_64_HASH);
+        assert_eq!(XXH3_64::hash_with_seed(SEED64, DATA), XXH3_64_SEEDED);
+        assert_eq!(
+            XXH3_64::hash_with_entropy(&SECRET_ENTROPY, DATA),
+            XXH3_64_KEYED
+        );
+        assert_eq!(
+            XXH3_64::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, DATA),
+            XXH3_64_KEYED_SEEDED
+        );
+        assert_eq!(XXH3_128::hash(DATA), XXH3_128_HASH);
+        assert_eq!(XXH3_128::hash_with_seed(SEED64, DATA), XXH3_128_SEEDED);
+        assert_eq!(
+            XXH3_128::hash_with_entropy(&SECRET_ENTROPY, DATA),
+            XXH3_128_KEYED
+        );
+        assert_eq!(
+            XXH3_128::hash_with_entropy_and_seed(&SECRET_ENTROPY, SEED64, DATA),
+            XXH3_128_KEYED_SEEDED
+        );
+
+        for sz in [1, 63, 64, 257, 1024, 4096].iter().copied() {
+            let mut h64 = XXH3_64::with_seed(SEED64);
+            let mut h128 = XXH3_128::with_seed(SEED64);
+            for chunk in DATA.chunks(sz) {
+                h64.write(chunk);
+                h128.write(chunk);
+            }
+            assert_eq!(h64.finish(), XXH3_64_SEEDED);
+            assert_eq!(h128.finish(), XXH3_128_SEEDED);
+        }
+    }
+    assert!(tested > 0 || cfg!(not(any(target_arch = "x86", target_arch = "x86_64"))));
+
+    set_backend(active).unwrap();
+    assert_eq!(active_backend(), active);
+}
+
+#[test]
+fn test_build_info() {
+    let info = build_info();
+    assert_eq!(info.pure_rust, cfg!(feature = "pure-rust"));
+    assert!(!(info.pure_rust && info.system_xxhash));
+    assert!(info.size_opt.unwrap_or(0) <= 2);
+    assert!(info.force_memory_access.unwrap_or(0) <= 3);
+    if info.system_xxhash {
+        assert_eq!(info.vector, Backend::Unknown);
+        assert_eq!(info.size_opt, None);
+    }
+    if !info.runtime_dispatch {
+        assert_eq!(active_backend(), info.vector);
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    assert!(!info.runtime_dispatch);
+}
+
+// Compile time checks of the auto traits of the public types
+fn assert_auto_traits<T: Send + Sync + Unpin>() {}
+
+#[test]
+fn test_auto_traits() {
+    fn check<T: Send + Sync + Unpin>(_: &T) {}
+
+    assert_auto_traits::<XXH32>();
+    assert_auto_traits::<XXH64>();
+    assert_auto_traits::<XXH3_64<'static>>();
+    assert_auto_traits::<XXH3_128<'static>>();
+    assert_auto_traits::<EntropyPool>();
+    #[cfg(feature = "alloc")]
+    assert_auto_traits::<CustomEntropyPool>();
+    assert_auto_traits::<FixedStateXXH32>();
+    assert_auto_traits::<FixedStateXXH64>();
+    assert_auto_traits::<FixedStateXXH3_64>();
+    assert_auto_traits::<FixedStateXXH3_128>();
+    #[cfg(all(feature = "random_entropy", feature = "std"))]
+    {
+        assert_auto_traits::<RandomStateXXH32>();
+        assert_auto_traits::<RandomStateXXH64>();
+        assert_auto_traits::<RandomStateXXH3_64>();
+        assert_auto_traits::<RandomStateXXH3_128>();
+        assert_auto_traits::<RandomStateXXH3_64Seeded>();
+        assert_auto_traits::<RandomStateXXH3_128Seeded>();
+    }
+    assert_auto_traits::<Hash32>();
+    assert_auto_traits::<Hash64>();
+    assert_auto_traits::<Hash128>();
+    assert_auto_traits::<Error>();
+    assert_auto_traits::<Backend>();
+    assert_auto_traits::<crate::buildinfo::BuildInfo>();
+    assert_auto_traits::<Mode>();
+
+    // Hashers borrowing their entropy pool
+    let pool = EntropyPool::with_key(b"My Custom Key!");
+    check(&XXH3_64::with_entropy_ref(&pool));
+    check(&XXH3_128::with_entropy_ref(&pool));
+}
+
+#[test]
+fn test_hasher_send() {
+    let entropy = EntropyPool::with_key(b"send");
+    let (a, b) = DATA.split_at(1000);
+
+    let mut h64 = XXH3_64::with_entropy(&entropy);
+    let mut h128 = XXH3_128::with_seed(SEED64);
+    h64.write(a);
+    h128.write(a);
+    let (r64, r128) = std::thread::spawn(move || {
+        h64.write(b);
+        h128.write(b);
+        (h64.finish(), h128.finish())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(r64, XXH3_64::hash_with_entropy(&entropy, DATA));
+    assert_eq!(r128, XXH3_128::hash_with_seed(SEED64, DATA));
+
+    // Hashers borrowing their entropy pool can be shared while it lives
+    let mut h = XXH3_64::with_entropy_ref(&entropy);
+    h.write(DATA);
+    let expected = XXH3_64::hash_with_entropy(&entropy, DATA);
+    let h = &h;
+    std::thread::scope(|s| {
+        s.spawn(move || assert_eq!(h.finish(), expected));
+    });
+}
+
+#[test]
+fn test_self_test() {
+    assert_eq!(self_test(), Ok(()));
+    if build_info().system_xxhash {
+        assert!(xxhash_version() >= 802);
+    } else {
+        assert_eq!(xxhash_version(), 802);
+    }
+}
+
+#[test]
+fn test_short_input_native() {
+    use crate::{
+        consthash::{K_SECRET, MIDSIZE_MAX},
+        xxh3::xxh128_to_u128,
+        C,
+    };
+    use std::os::raw::c_void;
+
+    assert_eq!(&EntropyPool::from_seed(0).entropy[..], &K_SECRET[..]);
+
+    let data = &DATA[..MIDSIZE_MAX + 1];
+    for len in 0..=data.len() {
+        let d = &data[..len];
+        let (ptr, len) = (d.as_ptr() as *const c_void, d.len());
+        for &seed in &[0, 1, SEED64, u64::MAX] {
+            let (c64, c128) = unsafe {
+                (
+                    C::XXH3_64bits_withSeed(ptr, len, seed),
+                    xxh128_to_u128(C::XXH3_128bits_withSeed(ptr, len, seed)),
+                )
+            };
+            assert_eq!(XXH3_64::hash_with_seed(seed, d), c64);
+            assert_eq!(XXH3_128::hash_with_seed(seed, d), c128);
+            if len <= MIDSIZE_MAX {
+                let e = &SECRET_ENTROPY;
+                assert_eq!(XXH3_64::hash_with_entropy_and_seed(e, seed, d), c64);
+                assert_eq!(XXH3_128::hash_with_entropy_and_seed(e, seed, d), c128);
+            }
+        }
+
+        for pool in &[
+            &K_SECRET[..],
+            &SECRET_ENTROPY.entropy,
+            &SECRET[..ENTROPY_POOL_SIZE_MIN],
+        ] {
+            let (c64, c128) = unsafe {
+                let (sptr, slen) = (pool.as_ptr() as *const c_void, pool.len());
+                (
+                    C::XXH3_64bits_withSecret(ptr, len, sptr, slen),
+                    xxh128_to_u128(C::XXH3_128bits_withSecret(ptr, len, sptr, slen)),
+                )
+            };
+            assert_eq!(unsafe { XXH3_64::hash_with_entropy_buffer(pool, d) }, c64);
+            assert_eq!(unsafe { XXH3_128::hash_with_entropy_buffer(pool, d) }, c128);
+        }
+
+        unsafe {
+            assert_eq!(XXH3_64::hash(d), C::XXH3_64bits(ptr, len));
+            assert_eq!(XXH3_128::hash(d), xxh128_to_u128(C::XXH3_128bits(ptr, len)));
+        }
+        let mut h = XXH3_64::with_entropy(&SECRET_ENTROPY);
+        h.write(d);
+        assert_eq!(XXH3_64::hash_with_entropy(&SECRET_ENTROPY, d), h.finish());
+    }
+}
+
+/// Deterministic xorshift64* generator for the randomized backend comparison
+#[cfg(not(feature = "pure-rust"))]
+struct Rng(u64);
+
+#[cfg(not(feature = "pure-rust"))]
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+#[test]
+#[cfg(not(feature = "pure-rust"))]
+fn test_native_backend() {
+    use crate::{consthash::MIDSIZE_MAX, native, xxh3::xxh128_to_u128, C};
+    use std::{mem::MaybeUninit, os::raw::c_void};
+
+    fn native128(val: native::XXH128_hash_t) -> u128 {
+        (val.low64 as u128) | (val.high64 as u128) << 64
+    }
+
+    fn check_one_shot(d: &[u8], secret: &[u8], seed: u64) {
+        let (p, l) = (d.as_ptr() as *const c_void, d.len());
+        let (sp, sl) = (secret.as_ptr() as *const c_void, secret.len());
+        unsafe {
+            let s32 = seed as u32;
+            assert_eq!(C::XXH32(p, l, s32), native::XXH32(p, l, s32));
+            assert_eq!(C::XXH64(p, l, seed), native::XXH64(p, l, seed));
+
+            assert_eq!(C::XXH3_64bits(p, l), native::XXH3_64bits(p, l));
+            assert_eq!(
+                C::XXH3_64bits_withSeed(p, l, seed),
+                native::XXH3_64bits_withSeed(p, l, seed)
+            );
+            assert_eq!(
+                C::XXH3_64bits_withSecret(p, l, sp, sl),
+                native::XXH3_64bits_withSecret(p, l, sp, sl)
+            );
+            assert_eq!(
+                C::XXH3_64bits_withSecretandSeed(p, l, sp, sl, seed),
+                native::XXH3_64bits_withSecretandSeed(p, l, sp, sl, seed)
+            );
+
+            assert_eq!(
+                xxh128_to_u128(C::XXH3_128bits(p, l)),
+                native128(native::XXH3_128bits(p, l))
+            );
+            assert_eq!(
+                xxh128_to_u128(C::XXH3_128bits_withSeed(p, l, seed)),
+                native128(native::XXH3_128bits_withSeed(p, l, seed))
+            );
+            assert_eq!(
+                xxh128_to_u128(C::XXH3_128bits_withSecret(p, l, sp, sl)),
+                native128(native::XXH3_128bits_withSecret(p, l, sp, sl))
+            );
+            assert_eq!(
+                xxh128_to_u128(C::XXH3_128bits_withSecretandSeed(p, l, sp, sl, seed)),
+                native128(native::XXH3_128bits_withSecretandSeed(p, l, sp, sl, seed))
+            );
+        }
+    }
+
+    // Feeds the chunks to both backends, comparing the digests after each
+    // chunk and again after resetting with the same configuration
+    fn check_streaming(chunks: &[&[u8]], secret: &[u8], seed: u64) {
+        macro_rules! stream {
+            ($state:ident, $update:ident, $digest:ident, $conv:expr, $nconv:expr,
+             $reset:ident($($arg:expr),*), $rereset:ident($($rearg:expr),*)) => {{
+                let mut c: C::$state = MaybeUninit::zeroed().assume_init();
+                let mut n: native::$state = MaybeUninit::zeroed().assume_init();
+                C::$reset(&mut c, $($arg),*);
+                native::$reset(&mut n, $($arg),*);
+                for _ in 0..2 {
+                    assert_eq!($conv(C::$digest(&c)), $nconv(native::$digest(&n)));
+                    for chunk in chunks {
+                        let (p, l) = (chunk.as_ptr() as *const c_void, chunk.len());
+                        C::$update(&mut c, p, l);
+                        native::$update(&mut n, p, l);
+                        assert_eq!($conv(C::$digest(&c)), $nconv(native::$digest(&n)));
+                    }
+                    C::$rereset(&mut c, $($rearg),*);
+                    native::$rereset(&mut n, $($rearg),*);
+                }
+            }};
+        }
+
+        macro_rules! stream3 {
+            ($reset:ident($($arg:expr),*)) => {{
+                stream!(XXH3_state_t, XXH3_64bits_update, XXH3_64bits_digest, |h| h, |h| h,
+                        $reset($($arg),*), XXHRS_64bits_reset_keepSecret());
+                stream!(XXH3_state_t, XXH3_128bits_update, XXH3_128bits_digest,
+                        xxh128_to_u128, native128,
+                        $reset($($arg),*), XXHRS_128bits_reset_keepSecret());
+            }};
+        }
+
+        let (sp, sl) = (secret.as_ptr() as *const c_void, secret.len());
+        let copy_len = min(sl, ENTROPY_POOL_SIZE);
+        let pool = SECRET_ENTROPY.entropy.as_ptr() as *const c_void;
+        unsafe {
+            stream!(
+                XXH32_state_t,
+                XXH32_update,
+                XXH32_digest,
+                |h| h,
+                |h| h,
+                XXH32_reset(seed as u32),
+                XXH32_reset(seed as u32)
+            );
+            stream!(
+                XXH64_state_t,
+                XXH64_update,
+                XXH64_digest,
+                |h| h,
+                |h| h,
+                XXH64_reset(seed),
+                XXH64_reset(seed)
+            );
+            stream3!(XXH3_64bits_reset());
+            stream3!(XXH3_64bits_reset_withSeed(seed));
+            stream3!(XXH3_64bits_reset_withSecret(sp, sl));
+            stream3!(XXHRS_64bits_reset_withSecretCopy(sp, copy_len));
+            stream3!(XXHRS_64bits_reset_withSecretandSeedCopy(pool, seed));
+        }
+    }
+
+    fn check_secret(key: &[u8], size: usize, seed: u64) {
+        let (mut c, mut n) = (vec![0u8; size], vec![0u8; size]);
+        let (kp, kl) = (key.as_ptr() as *const c_void, key.len());
+        unsafe {
+            C::XXH3_generateSecret(c.as_mut_ptr() as *mut c_void, size, kp, kl);
+            native::XXH3_generateSecret(n.as_mut_ptr() as *mut c_void, size, kp, kl);
+            assert_eq!(c, n);
+
+            let (mut c, mut n) = ([0u8; ENTROPY_POOL_SIZE], [0u8; ENTROPY_POOL_SIZE]);
+            C::XXH3_generateSecret_fromSeed(c.as_mut_ptr() as *mut c_void, seed);
+            native::XXH3_generateSecret_fromSeed(n.as_mut_ptr() as *mut c_void, seed);
+            assert_eq!(c, n);
+        }
+    }
+
+    // Fixtures
+    let pools: [&[u8]; 3] = [
+        SECRET,
+        &SECRET_ENTROPY.entropy,
+        &SECRET[..ENTROPY_POOL_SIZE_MIN],
+    ];
+    for len in (0..=1100).chain(Some(DATA.len())) {
+        for &pool in &pools {
+            for &seed in &[0, SEED64, u64::MAX] {
+                check_one_shot(&DATA[..len], pool, seed);
+            }
+        }
+    }
+    for &pool in &pools {
+        for &sz in &[1, 7, 64, 89, 256, 1000] {
+            let chunks: Vec<&[u8]> = DATA.chunks(sz).collect();
+            check_streaming(&chunks, pool, SEED64);
+        }
+        check_secret(pool, ENTROPY_POOL_SIZE, SEED64);
+    }
+    check_secret(b"", ENTROPY_POOL_SIZE, 0);
+
+    // Randomized inputs
+    let mut rng = Rng(0x2f4a_1c3b_9d8e_7f60);
+    for _ in 0..200 {
+        let len = match rng.below(3) {
+            0 => rng.below(MIDSIZE_MAX + 2),
+            1 => rng.below(2048),
+            _ => rng.below(DATA.len()),
+        };
+        let data = rng.bytes(len);
+        let secret_len = ENTROPY_POOL_SIZE_MIN + rng.below(200);
+        let secret = rng.bytes(secret_len);
+        let seed = match rng.below(3) {
+            0 => 0,
+            _ => rng.next(),
+        };
+
+        check_one_shot(&data, &secret, seed);
+
+        let mut chunks = Vec::new();
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let sz = match rng.below(4) {
+                0 => rng.below(600),
+                _ => rng.below(70),
+            };
+            let (head, tail) = rest.split_at(min(sz, rest.len()));
+            chunks.push(head);
+            rest = tail;
+        }
+        check_streaming(&chunks, &secret, seed);
+
+        let key_len = rng.below(300);
+        let key = rng.bytes(key_len);
+        check_secret(&key, ENTROPY_POOL_SIZE_MIN + rng.below(200), seed);
+
+        let h = rng.next();
+        unsafe {
+            let mut c = C::XXH128_canonical_t { digest: [0; 16] };
+            let mut n = native::XXH128_canonical_t { digest: [0; 16] };
+            let val = (h, rng.next());
+            C::XXH128_canonicalFromHash(
+                &mut c,
+                C::XXH128_hash_t {
+                    low64: val.0,
+                    high64: val.1,
+                },
+            );
+            native::XXH128_canonicalFromHash(
+                &mut n,
+                native::XXH128_hash_t {
+                    low64: val.0,
+                    high64: val.1,
+                },
+            );
+            assert_eq!(c.digest, n.digest);
+            assert_eq!(
+                xxh128_to_u128(C::XXH128_hashFromCanonical(&c)),
+                native128(native::XXH128_hashFromCanonical(&n))
+            );
+            let mut c = C::XXH64_canonical_t { digest: [0; 8] };
+            let mut n = native::XXH64_canonical_t { digest: [0; 8] };
+            C::XXH64_canonicalFromHash(&mut c, h);
+            native::XXH64_canonicalFromHash(&mut n, h);
+            assert_eq!(c.digest, n.digest);
+            assert_eq!(
+                C::XXH64_hashFromCanonical(&c),
+                native::XXH64_hashFromCanonical(&n)
+            );
+            let mut c = C::XXH32_canonical_t { digest: [0; 4] };
+            let mut n = native::XXH32_canonical_t { digest: [0; 4] };
+            C::XXH32_canonicalFromHash(&mut c, h as u32);
+            native::XXH32_canonicalFromHash(&mut n, h as u32);
+            assert_eq!(c.digest, n.digest);
+            assert_eq!(
+                C::XXH32_hashFromCanonical(&c),
+                native::XXH32_hashFromCanonical(&n)
+            );
+        }
+    }
+}
+
 #[test]
 fn test_streaming() {
     const BLOCK_SIZE: &[usize] = &[0, 1, 2, 3, 4, 7, 11, 31, 63, 89];
@@ -158,6 +912,11 @@ fn test_streaming() {
         XXH3_128_SEEDED <- XXH3_128::with_entropy(&SEED64_ENTROPY),
         XXH3_128_KEYED  <- XXH3_128::with_entropy(&SECRET_ENTROPY),
 
+        XXH3_64_SEEDED  <- XXH3_64::with_seed(SEED64),
+        XXH3_128_SEEDED <- XXH3_128::with_seed(SEED64),
+        XXH3_64_KEYED_SEEDED  <- XXH3_64::with_entropy_and_seed(&SECRET_ENTROPY, SEED64),
+        XXH3_128_KEYED_SEEDED <- XXH3_128::with_entropy_and_seed(&SECRET_ENTROPY, SEED64),
+
         // Entropy cloning
         XXH3_64_SEEDED <- { XXH3_64::with_entropy( &SEED64_ENTROPY.clone() )},
         XXH3_64_KEYED  <- { XXH3_64::with_entropy( &SECRET_ENTROPY.clone() )},
@@ -178,6 +937,14 @@ fn test_streaming() {
             XXH3_128_KEYED  <- { XXH3_128::with_entropy_buffer(&SECRET_ENTROPY.entropy) },
         );
     }
+
+    // Borrowed entropy
+    test_stream!(
+        XXH3_64_SEEDED <- { XXH3_64::with_entropy_ref(&SEED64_ENTROPY) },
+        XXH3_64_KEYED  <- { XXH3_64::with_entropy_ref(&SECRET_ENTROPY) },
+        XXH3_128_SEEDED <- { XXH3_128::with_entropy_ref(&SEED64_ENTROPY) },
+        XXH3_128_KEYED  <- { XXH3_128::with_entropy_ref(&SECRET_ENTROPY) },
+    );
 }
 
 #[test]
@@ -190,10 +957,419 @@ fn test_hasher_iface() {
     assert_eq!(Hasher::finish(&h3), XXH3_64_SEEDED);
 }
 
+#[test]
+fn test_hasher_small_writes() {
+    // Lots of integer writes, first filling and then overflowing the
+    // internal buffers; the result must match hashing the whole input.
+    let mut data = Vec::new();
+    let mut h64 = XXH64::with_seed(SEED64);
+    let mut h3_64 = XXH3_64::with_seed(SEED64);
+    let mut h3_64_keyed = XXH3_64::with_entropy(&SECRET_ENTROPY);
+    let mut h3_128 = XXH3_128::new();
+    for ix in 0u32..200 {
+        data.extend_from_slice(&ix.to_ne_bytes());
+        data.extend_from_slice(&(ix as u64).to_ne_bytes());
+        data.extend_from_slice(&(ix as usize).to_ne_bytes());
+        data.push(ix as u8);
+        for h in [&mut h64 as &mut dyn Hasher, &mut h3_64, &mut h3_64_keyed] {
+            h.write_u32(ix);
+            h.write_u64(ix as u64);
+            h.write_usize(ix as usize);
+            h.write_u8(ix as u8);
+        }
+        h3_128.write(&data[data.len() - 4 - 8 - 8 - 1..]);
+
+        assert_eq!(Hasher::finish(&h64), XXH64::hash_with_seed(SEED64, &data));
+        assert_eq!(
+            Hasher::finish(&h3_64),
+            XXH3_64::hash_with_seed(SEED64, &data)
+        );
+        assert_eq!(
+            Hasher::finish(&h3_64_keyed),
+            XXH3_64::hash_with_entropy(&SECRET_ENTROPY, &data)
+        );
+        assert_eq!(h3_128.finish(), XXH3_128::hash(&data));
+    }
+}
+
+#[test]
+fn test_reset() {
+    macro_rules! check {
+        ($h:expr, $out:expr) => {{
+            $h.write(DATA);
+            assert_eq!($h.finish(), $out);
+        }};
+    }
+
+    let mut h = XXH32::new();
+    h.write(&DATA[..100]);
+    h.reset_with_seed(SEED32);
+    check!(h, XXH32_SEEDED);
+    h.reset();
+    check!(h, XXH32_SEEDED);
+    h.reset_with_seed(0);
+    check!(h, XXH32_HASH);
+
+    let mut h = XXH64::new();
+    h.write(&DATA[..100]);
+    h.reset_with_seed(SEED64);
+    check!(h, XXH64_SEEDED);
+    h.reset();
+    check!(h, XXH64_SEEDED);
+    h.reset_with_seed(0);
+    check!(h, XXH64_HASH);
+
+    macro_rules! check_xxh3 {
+        ($typ:ident, $hash:expr, $seeded:expr, $keyed:expr, $keyed_seeded:expr) => {{
+            let mut h = $typ::new();
+            h.write(&DATA[..100]);
+            h.reset_with_seed(SEED64);
+            check!(h, $seeded);
+            h.reset();
+            check!(h, $seeded);
+            h.reset_with_entropy(&SECRET_ENTROPY);
+            check!(h, $keyed);
+            h.reset();
+            check!(h, $keyed);
+            h.reset_with_seed(0);
+            check!(h, $hash);
+
+            // customSecret holds the entropy pool, not the secret derived
+            // from the seed
+            let mut h = $typ::with_entropy_and_seed(&SECRET_ENTROPY, SEED64);
+            check!(h, $keyed_seeded);
+            h.reset_with_seed(SEED64);
+            check!(h, $seeded);
+
+            let mut h = $typ::with_entropy_ref(&SECRET_ENTROPY);
+            h.reset_with_seed(SEED64);
+            check!(h, $seeded);
+        }};
+    }
+
+    check_xxh3!(
+        XXH3_64,
+        XXH3_64_HASH,
+        XXH3_64_SEEDED,
+        XXH3_64_KEYED,
+        XXH3_64_KEYED_SEEDED
+    );
+    check_xxh3!(
+        XXH3_128,
+        XXH3_128_HASH,
+        XXH3_128_SEEDED,
+        XXH3_128_KEYED,
+        XXH3_128_KEYED_SEEDED
+    );
+}
+
+#[test]
+fn test_saved_state() {
+    // Inside the buffer, across blocks and stripes, at the very end
+    const SPLITS: &[usize] = &[0, 7, 100, 255, 1024, 1500, 9973];
+
+    macro_rules! check {
+        ($mk:expr, $out:expr) => {{
+            for &at in SPLITS {
+                let mut h = $mk;
+                h.write(&DATA[..at]);
+                let saved = h.save_state();
+
+                let mut h = $mk;
+                h.write(&DATA[..SHORT]);
+                h.restore_state(&saved).unwrap();
+                h.write(&DATA[at..]);
+                assert_eq!(h.finish(), $out);
+            }
+        }};
+    }
+
+    check!(XXH32::new(), XXH32_HASH);
+    check!(XXH32::with_seed(SEED32), XXH32_SEEDED);
+    check!(XXH64::new(), XXH64_HASH);
+    check!(XXH64::with_seed(SEED64), XXH64_SEEDED);
+    check!(XXH3_64::new(), XXH3_64_HASH);
+    check!(XXH3_64::with_seed(SEED64), XXH3_64_SEEDED);
+    check!(XXH3_64::with_entropy(&SECRET_ENTROPY), XXH3_64_KEYED);
+    check!(
+        XXH3_64::with_entropy_and_seed(&SECRET_ENTROPY, SEED64),
+        XXH3_64_KEYED_SEEDED
+    );
+    check!(XXH3_128::new(), XXH3_128_HASH);
+    check!(XXH3_128::with_seed(SEED64), XXH3_128_SEEDED);
+    check!(XXH3_128::with_entropy(&SECRET_ENTROPY), XXH3_128_KEYED);
+    check!(
+        XXH3_128::with_entropy_and_seed(&SECRET_ENTROPY, SEED64),
+        XXH3_128_KEYED_SEEDED
+    );
+
+    #[cfg(feature = "alloc")]
+    {
+        let pool = CustomEntropyPool::try_from(SECRET).unwrap();
+        check!(
+            XXH3_64::with_custom_entropy(&pool),
+            XXH3_64::hash_with_custom_entropy(&pool, DATA)
+        );
+        check!(
+            XXH3_128::with_custom_entropy(&pool),
+            XXH3_128::hash_with_custom_entropy(&pool, DATA)
+        );
+    }
+
+    // Seeded states reseed the hasher they are restored into
+    let mut h = XXH3_64::with_seed(SEED64);
+    h.write(&DATA[..SHORT]);
+    let saved = h.save_state();
+    let mut h = XXH3_64::with_entropy(&SECRET_ENTROPY);
+    h.restore_state(&saved).unwrap();
+    h.write(&DATA[SHORT..]);
+    assert_eq!(h.finish(), XXH3_64_SEEDED);
+
+    let mut h = XXH64::new();
+    h.write(&DATA[..SHORT]);
+    let saved = h.save_state();
+    let mut h = XXH64::with_seed(SEED64);
+    h.restore_state(&saved).unwrap();
+    h.write(&DATA[SHORT..]);
+    assert_eq!(h.finish(), XXH64_HASH);
+
+    // The format is fixed
+    let mut h = XXH32::with_seed(SEED32);
+    h.write(b"MyData");
+    let saved = h.save_state();
+    assert_eq!(&saved[..8], b"XXHS\x01\x00\x01\x00");
+    assert_eq!(
+        &saved[8..24],
+        &[0x71, 0x98, 0x64, 0xf7, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(&saved[40..46], b"MyData");
+}
+
+#[test]
+fn test_saved_state_errors() {
+    let mut h = XXH3_64::with_entropy(&SECRET_ENTROPY);
+    h.write(&DATA[..1500]);
+    let saved = h.save_state();
+
+    // The hasher must be left alone on failure
+    let mut h = XXH3_64::with_entropy(&SECRET_ENTROPY);
+    h.write(&DATA[..1500]);
+    let check_unchanged = |h: &XXH3_64| {
+        let mut h = h.clone();
+        h.write(&DATA[1500..]);
+        assert_eq!(h.finish(), XXH3_64_KEYED);
+    };
+
+    // Entropy pools must match
+    assert_eq!(
+        XXH3_64::new().restore_state(&saved),
+        Err(Error::EntropyPoolMismatch)
+    );
+    assert_eq!(
+        XXH3_64::with_seed(SEED64).restore_state(&saved),
+        Err(Error::EntropyPoolMismatch)
+    );
+    assert_eq!(
+        XXH3_64::with_entropy(&SEED64_ENTROPY).restore_state(&saved),
+        Err(Error::EntropyPoolMismatch)
+    );
+    #[cfg(feature = "alloc")]
+    assert_eq!(
+        XXH3_64::with_custom_entropy(&CustomEntropyPool::try_from(SECRET).unwrap())
+            .restore_state(&saved),
+        Err(Error::EntropyPoolMismatch)
+    );
+
+    // Other algorithms, truncation and corruption
+    assert_eq!(
+        XXH3_128::with_entropy(&SECRET_ENTROPY).restore_state(&saved),
+        Err(Error::InvalidSavedState)
+    );
+    assert_eq!(
+        XXH64::new().restore_state(&XXH32::new().save_state()),
+        Err(Error::InvalidSavedState)
+    );
+    assert_eq!(
+        XXH32::new().restore_state(&XXH64::new().save_state()),
+        Err(Error::InvalidSavedState)
+    );
+    assert_eq!(
+        h.restore_state(&saved[..saved.len() - 1]),
+        Err(Error::InvalidSavedState)
+    );
+    assert_eq!(h.restore_state(&[]), Err(Error::InvalidSavedState));
+    for (off, val) in [
+        (0, b'x'),   // magic
+        (4, 2),      // version
+        (6, 4),      // mode
+        (7, 1),      // reserved
+        (345, 0xff), // bufferedSize
+        (348, 1),    // reserved
+        (352, 16),   // nbStripesSoFar
+    ] {
+        let mut corrupted = saved;
+        corrupted[off] = val;
+        assert_eq!(h.restore_state(&corrupted), Err(Error::InvalidSavedState));
+    }
+    let mut corrupted = saved;
+    corrupted[368] ^= 1; // fingerprint
+    assert_eq!(h.restore_state(&corrupted), Err(Error::EntropyPoolMismatch));
+    check_unchanged(&h);
+
+    let mut corrupted = XXH32::new().save_state();
+    corrupted[56] = 3; // memsize
+    assert_eq!(
+        XXH32::new().restore_state(&corrupted),
+        Err(Error::InvalidSavedState)
+    );
+    let mut corrupted = XXH64::new().save_state();
+    corrupted[92] = 1; // reserved
+    assert_eq!(
+        XXH64::new().restore_state(&corrupted),
+        Err(Error::InvalidSavedState)
+    );
+}
+
+#[test]
+fn test_streaming_hasher_iface() {
+    fn check<H>(mut h: H, out: H::Output)
+    where
+        H: StreamingHasher + Clone,
+        H::Output: PartialEq + std::fmt::Debug,
+    {
+        let empty = h.finish();
+        StreamingHasher::write(&mut h, &DATA[..100]);
+        StreamingHasher::write(&mut h, &DATA[100..]);
+        assert_eq!(StreamingHasher::finish(&h), out);
+
+        let mut h2 = h.clone();
+        StreamingHasher::reset(&mut h);
+        assert_eq!(StreamingHasher::finish(&h), empty);
+        StreamingHasher::write(&mut h, DATA);
+        assert_eq!(StreamingHasher::finish(&h), out);
+
+        StreamingHasher::reset(&mut h2);
+        StreamingHasher::reset(&mut h2); // can reset twice
+        StreamingHasher::write(&mut h2, DATA);
+        assert_eq!(StreamingHasher::finish(&h2), out);
+    }
+
+    check(XXH32::new(), XXH32_HASH);
+    check(XXH32::with_seed(SEED32), XXH32_SEEDED);
+    check(XXH64::new(), XXH64_HASH);
+    check(XXH64::with_seed(SEED64), XXH64_SEEDED);
+    check(XXH3_64::new(), XXH3_64_HASH);
+    check(XXH3_64::with_seed(SEED64), XXH3_64_SEEDED);
+    check(XXH3_64::with_entropy(&SECRET_ENTROPY), XXH3_64_KEYED);
+    check(XXH3_128::new(), XXH3_128_HASH);
+    check(XXH3_128::with_seed(SEED64), XXH3_128_SEEDED);
+    check(XXH3_128::with_entropy(&SECRET_ENTROPY), XXH3_128_KEYED);
+    check(
+        XXH3_64::with_entropy_and_seed(&SECRET_ENTROPY, SEED64),
+        XXH3_64_KEYED_SEEDED,
+    );
+    check(
+        XXH3_128::with_entropy_and_seed(&SECRET_ENTROPY, SEED64),
+        XXH3_128_KEYED_SEEDED,
+    );
+
+    unsafe {
+        check(
+            XXH3_64::with_entropy_buffer(&SECRET_ENTROPY.entropy),
+            XXH3_64_KEYED,
+        );
+        check(
+            XXH3_128::with_entropy_buffer(&SECRET_ENTROPY.entropy),
+            XXH3_128_KEYED,
+        );
+    }
+}
+
+#[test]
+fn test_streaming_entropy_and_seed_short() {
+    let mut h = XXH3_64::with_entropy_and_seed(&SECRET_ENTROPY, SEED64);
+    h.write(&DATA[..SHORT]);
+    assert_eq!(h.finish(), XXH3_64_KEYED_SEEDED_SHORT);
+
+    let mut h = XXH3_128::with_entropy_and_seed(&SECRET_ENTROPY, SEED64);
+    h.write(&DATA[..SHORT]);
+    assert_eq!(h.finish(), XXH3_128_KEYED_SEEDED_SHORT);
+
+    // Seed zero still uses the seeded (default secret) path for short inputs
+    assert_eq!(
+        XXH3_64::hash_with_entropy_and_seed(&SECRET_ENTROPY, 0, &DATA[..SHORT]),
+        XXH3_64_KEYED_SEED0_SHORT
+    );
+    let mut h = XXH3_64::with_entropy_and_seed(&SECRET_ENTROPY, 0);
+    h.write(&DATA[..SHORT]);
+    assert_eq!(h.finish(), XXH3_64_KEYED_SEED0_SHORT);
+    StreamingHasher::reset(&mut h);
+    h.write(&DATA[..SHORT]);
+    assert_eq!(h.finish(), XXH3_64_KEYED_SEED0_SHORT);
+    assert_eq!(XXH3_64_KEYED_SEED0_SHORT, XXH3_64::hash(&DATA[..SHORT]));
+
+    assert_eq!(
+        XXH3_128::hash_with_entropy_and_seed(&SECRET_ENTROPY, 0, &DATA[..SHORT]),
+        XXH3_128_KEYED_SEED0_SHORT
+    );
+    let mut h = XXH3_128::with_entropy_and_seed(&SECRET_ENTROPY, 0);
+    h.write(&DATA[..SHORT]);
+    assert_eq!(h.finish(), XXH3_128_KEYED_SEED0_SHORT);
+    StreamingHasher::reset(&mut h);
+    h.write(&DATA[..SHORT]);
+    assert_eq!(h.finish(), XXH3_128_KEYED_SEED0_SHORT);
+    assert_eq!(XXH3_128_KEYED_SEED0_SHORT, XXH3_128::hash(&DATA[..SHORT]));
+}
+
+#[test]
+fn test_digest() {
+    assert_eq!(XXH32::new().digest(), Hash32(XXH32::hash(b"")));
+    assert_eq!(XXH64::new().digest(), Hash64(XXH64::hash(b"")));
+    assert_eq!(XXH3_64::new().digest(), Hash64(XXH3_64::hash(b"")));
+    assert_eq!(XXH3_128::new().digest(), Hash128(XXH3_128::hash(b"")));
+
+    let h32 = Hash32(XXH32_HASH);
+    let h64 = Hash64(XXH3_64_HASH);
+    let h128 = Hash128(XXH3_128_HASH);
+
+    assert_eq!(h32.to_canonical(), XXH32_HASH.to_be_bytes());
+    assert_eq!(h64.to_canonical(), XXH3_64_HASH.to_be_bytes());
+    assert_eq!(h128.to_canonical(), XXH3_128_HASH.to_be_bytes());
+    assert_eq!(Hash32::from_canonical(h32.to_canonical()), h32);
+    assert_eq!(Hash64::from_canonical(h64.to_canonical()), h64);
+    assert_eq!(Hash128::from_canonical(h128.to_canonical()), h128);
+
+    assert_eq!(h32.to_string(), "f466cd9b");
+    assert_eq!(h64.to_string(), "8a24edfe37edf1ea");
+    assert_eq!(h128.to_string(), "085fd9804f34051d8a24edfe37edf1ea");
+    assert_eq!(Hash32(1).to_string(), "00000001");
+    assert_eq!(format!("{:x}", Hash64(1)), "0000000000000001");
+    assert_eq!(format!("{:#X}", h32), "0xF466CD9B");
+
+    assert_eq!("f466cd9b".parse(), Ok(h32));
+    assert_eq!("F466CD9B".parse(), Ok(h32));
+    assert_eq!("8a24edfe37edf1ea".parse(), Ok(h64));
+    assert_eq!("085fd9804f34051d8a24edfe37edf1ea".parse(), Ok(h128));
+    assert!("".parse::<Hash32>().is_err());
+    assert!("f466cd9".parse::<Hash32>().is_err());
+    assert!("f466cd9b0".parse::<Hash32>().is_err());
+    assert!("+466cd9b".parse::<Hash32>().is_err());
+    assert!("0xf466cd9b".parse::<Hash32>().is_err());
+    assert!("f466cd9b".parse::<Hash64>().is_err());
+    assert_eq!("".parse::<Hash128>(), Err(Error::ParseHash));
+
+    assert_eq!(u32::from(h32), XXH32_HASH);
+    assert_eq!(Hash128::from(XXH3_128_HASH), h128);
+}
+
 #[test]
 #[cfg(feature = "random_entropy")]
 fn test_random_entropy_pool() {
     assert_ne!(EntropyPool::randomize(), EntropyPool::randomize());
+    assert_ne!(
+        EntropyPool::try_randomize().unwrap(),
+        EntropyPool::try_randomize().unwrap()
+    );
     assert_ne!(
         XXH3_128::hash_with_entropy(&EntropyPool::randomize(), b""),
         XXH3_128::hash_with_entropy(&EntropyPool::randomize(), b"")
@@ -208,7 +1384,7 @@ fn test_entropy_pool_clone() {
 }
 
 #[test]
-#[cfg(feature = "random_entropy")]
+#[cfg(all(feature = "random_entropy", feature = "std"))]
 fn test_build_hasher() {
     let mut set = HashSet::<u128>::new();
 
@@ -235,6 +1411,7 @@ fn test_build_hasher() {
             for _ in 0..10 {
                 test_random_state_instance!(<$typ>::new());
                 test_random_state_instance!(<$typ>::default());
+                test_random_state_instance!(<$typ>::try_new().unwrap());
             }
         }};
     }
@@ -243,10 +1420,91 @@ fn test_build_hasher() {
     test_random_state!(RandomStateXXH64);
     test_random_state!(RandomStateXXH3_64);
     test_random_state!(RandomStateXXH3_128);
+    test_random_state!(RandomStateXXH3_64Seeded);
+    test_random_state!(RandomStateXXH3_128Seeded);
+
+    let seeded = RandomStateXXH3_64Seeded::new();
+    assert_eq!(
+        hash_now!(seeded, DATA),
+        XXH3_64::hash_with_seed(seeded.seed(), DATA)
+    );
+
+    // build_hasher() copies the prepared hasher, which must stay unused
+    let seeded = RandomStateXXH3_64Seeded::with_seed(SEED64);
+    assert_eq!(hash_now!(seeded, DATA), XXH3_64_SEEDED);
+    assert_eq!(hash_now!(seeded.clone(), DATA), XXH3_64_SEEDED);
+    let seeded = RandomStateXXH3_128Seeded::with_seed(SEED64);
+    assert_eq!(hash_now!(seeded, DATA), XXH3_128_SEEDED);
+    assert_eq!(hash_now!(seeded, DATA), XXH3_128_SEEDED);
+    let seeded = RandomStateXXH3_128Seeded::with_seed(0);
+    assert_eq!(hash_now!(seeded, DATA), XXH3_128_HASH);
+    assert_eq!(seeded, RandomStateXXH3_128Seeded::with_seed(0));
 }
 
 #[test]
-#[cfg(feature = "random_entropy")]
+#[cfg(all(feature = "random_entropy", feature = "std"))]
+fn test_master_pool() {
+    let mut pools = HashSet::<Vec<u8>>::new();
+    let mut seeds = HashSet::<u64>::new();
+    for _ in 0..1000 {
+        assert!(pools.insert(derive_pool().unwrap().entropy.to_vec()));
+        assert!(seeds.insert(derive_seed().unwrap()));
+        assert!(pools.insert(RandomStateXXH3_128::new().pool.entropy.to_vec()));
+        assert!(seeds.insert(RandomStateXXH64::new().seed));
+    }
+}
+
+#[test]
+fn test_fixed_state() {
+    macro_rules! hash_now {
+        ($bh:expr, $val:expr) => {{
+            let mut hasher = $bh.build_hasher();
+            hasher.write($val);
+            hasher.finish()
+        }};
+    }
+
+    let s32 = FixedStateXXH32 { seed: SEED32 };
+    let s64 = FixedStateXXH64 { seed: SEED64 };
+    let s3_64 = FixedStateXXH3_64 {
+        pool: SECRET_ENTROPY.clone(),
+    };
+    let s3_128 = FixedStateXXH3_128 {
+        pool: SECRET_ENTROPY.clone(),
+    };
+    assert_eq!(hash_now!(s32, DATA), XXH32_SEEDED);
+    assert_eq!(hash_now!(s64, DATA), XXH64_SEEDED);
+    assert_eq!(hash_now!(s3_64, DATA), XXH3_64_KEYED);
+    assert_eq!(hash_now!(s3_128, DATA), XXH3_128_KEYED);
+    assert_eq!(hash_now!(s3_128.clone(), DATA), XXH3_128_KEYED);
+
+    let d = &DATA[..SHORT];
+    assert_eq!(hash_now!(FixedStateXXH32::default(), d), XXH32::hash(d));
+    assert_eq!(hash_now!(FixedStateXXH64::default(), d), XXH64::hash(d));
+    assert_eq!(hash_now!(FixedStateXXH3_64::default(), d), XXH3_64::hash(d));
+    assert_eq!(
+        hash_now!(FixedStateXXH3_128::default(), d),
+        XXH3_128::hash(d)
+    );
+    assert_eq!(
+        hash_now!(FixedStateXXH3_128::default(), DATA),
+        XXH3_128_HASH
+    );
+
+    let mut hm = HashMap::<u64, u64, FixedStateXXH64>::default();
+    let mut hs = HashSet::<u64, FixedStateXXH3_64>::default();
+    for ix in 0..1024 {
+        assert_eq!(hm.insert(ix, 42), None);
+        assert!(hs.insert(ix));
+    }
+    assert_eq!(
+        s64.hash_one(42u64),
+        FixedStateXXH64 { seed: SEED64 }.hash_one(42u64)
+    );
+}
+
+#[test]
+#[cfg(all(feature = "random_entropy", feature = "std"))]
 fn test_hash_set() {
     macro_rules! test_random_state {
         ($typ:ty) => {{
@@ -264,7 +1522,80 @@ fn test_hash_set() {
 }
 
 #[test]
-#[cfg(feature = "random_entropy")]
+fn test_introspection() {
+    let mut h = XXH32::new();
+    assert_eq!(h.mode(), Mode::Unseeded);
+    h.write(b"MyData");
+    assert_eq!(h.total_len(), 6);
+    assert_eq!(format!("{:?}", h), "XXH32 { mode: Unseeded, total_len: 6 }");
+    h.reset_with_seed(42);
+    assert_eq!(format!("{:?}", h), "XXH32 { mode: Seed(..), total_len: 0 }");
+
+    let mut h = XXH64::with_seed(SEED64);
+    assert_eq!(h.mode(), Mode::Seed(SEED64));
+    h.write(DATA);
+    assert_eq!(h.total_len(), DATA.len() as u64);
+    assert_eq!(
+        format!("{:?}", XXH64::with_seed(42)),
+        "XXH64 { mode: Seed(..), total_len: 0 }"
+    );
+
+    macro_rules! check_xxh3 {
+        ($typ:ident) => {{
+            assert_eq!($typ::new().mode(), Mode::Unseeded);
+            assert_eq!($typ::with_seed(0).mode(), Mode::Unseeded);
+            assert_eq!($typ::with_seed(SEED64).mode(), Mode::Seed(SEED64));
+            assert_eq!(
+                $typ::with_entropy_ref(&SECRET_ENTROPY).mode(),
+                Mode::Entropy
+            );
+            assert_eq!(
+                $typ::with_entropy_and_seed(&SECRET_ENTROPY, 0).mode(),
+                Mode::EntropyAndSeed(0)
+            );
+
+            // Short writes are buffered without calling into xxhash
+            let mut h = $typ::with_entropy(&SECRET_ENTROPY);
+            h.write(&DATA[..SHORT]);
+            assert_eq!(h.total_len(), SHORT as u64);
+            h.write(DATA);
+            assert_eq!(h.total_len(), (SHORT + DATA.len()) as u64);
+            assert_eq!(
+                format!("{:?}", h),
+                format!(
+                    "{} {{ mode: Entropy, total_len: {} }}",
+                    stringify!($typ),
+                    SHORT + DATA.len()
+                )
+            );
+
+            h.reset_with_seed(42);
+            assert_eq!(h.mode(), Mode::Seed(42));
+            assert_eq!(h.total_len(), 0);
+            assert_eq!(
+                format!("{:?}", $typ::with_entropy_and_seed(&SECRET_ENTROPY, 42)),
+                format!(
+                    "{} {{ mode: EntropyAndSeed(..), total_len: 0 }}",
+                    stringify!($typ)
+                )
+            );
+            h.reset_with_entropy(&SECRET_ENTROPY);
+            assert_eq!(h.mode(), Mode::Entropy);
+
+            #[cfg(feature = "alloc")]
+            assert_eq!(
+                $typ::with_custom_entropy(&CustomEntropyPool::try_from(SECRET).unwrap()).mode(),
+                Mode::Entropy
+            );
+        }};
+    }
+
+    check_xxh3!(XXH3_64);
+    check_xxh3!(XXH3_128);
+}
+
+#[test]
+#[cfg(all(feature = "random_entropy", feature = "std"))]
 fn test_debug_print() {
     macro_rules! assert_debug {
         ($in:expr, $out:expr) => {
@@ -1,16 +1,245 @@
-use crate::{entropy::EntropyPool, C};
-use std::{default::Default, hash::Hasher, marker::PhantomData, mem::MaybeUninit, os::raw::c_void};
+use crate::{
+    consthash::{
+        xxh3_128_short, xxh3_64_short, K_SECRET, MIDSIZE_MAX, SECRET_CONSUME_RATE, STRIPE_LEN,
+    },
+    digest::{Hash128, Hash64},
+    entropy::{EntropyPool, ENTROPY_POOL_SIZE},
+    error::Error,
+    hasher::{Mode, RedactedMode, StreamingHasher},
+    savedstate::{Algorithm, StateReader, StateWriter, XXH3_SAVED_STATE_SIZE},
+    C,
+};
+use core::{
+    default::Default, ffi::c_void, fmt, hash::Hasher, marker::PhantomData, mem::MaybeUninit, ptr,
+    slice,
+};
+
+#[cfg(feature = "alloc")]
+use crate::entropy::CustomEntropyPool;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+
+/// Entropy owned by a hasher; state.extSecret points into it.
+///
+/// Without the alloc feature, hashers never own their entropy.
+#[derive(Clone)]
+enum OwnedEntropy {
+    /// Custom entropy pools too large to be copied into the state
+    #[cfg(feature = "alloc")]
+    Boxed(Box<[u8]>),
+}
+
+/// Append short inputs to the internal buffer of the state, just like
+/// XXH3_update would, so writing small keys does not require an FFI call.
+///
+/// Returns false if the input does not fit into the buffer; the caller
+/// must call XXH3_update in that case.
+#[inline]
+fn buffer_short_write(state: &mut C::XXH3_state_t, bytes: &[u8]) -> bool {
+    let buffered = state.bufferedSize as usize;
+    if bytes.len() > C::XXH3_INTERNALBUFFER_SIZE as usize - buffered {
+        return false;
+    }
+    state.buffer[buffered..buffered + bytes.len()].copy_from_slice(bytes);
+    state.bufferedSize += bytes.len() as u32;
+    state.totalLen += bytes.len() as u64;
+    true
+}
+
+fn mode(state: &C::XXH3_state_t, entropy_pool: bool) -> Mode {
+    match (entropy_pool, state.useSeed != 0) {
+        (false, false) => Mode::Unseeded,
+        (false, true) => Mode::Seed(state.seed),
+        (true, false) => Mode::Entropy,
+        (true, true) => Mode::EntropyAndSeed(state.seed),
+    }
+}
+
+/// The secret in use; the copy in customSecret unless extSecret is set
+fn secret(state: &C::XXH3_state_t) -> &[u8] {
+    let len = state.secretLimit + STRIPE_LEN;
+    if state.extSecret.is_null() {
+        &state.customSecret[..len]
+    } else {
+        unsafe { slice::from_raw_parts(state.extSecret, len) }
+    }
+}
+
+/// Saved states identify the entropy pool by its hash
+fn fingerprint(mode: Mode, secret: &[u8]) -> u64 {
+    match mode {
+        Mode::Entropy | Mode::EntropyAndSeed(_) => XXH3_64::hash(secret),
+        Mode::Unseeded | Mode::Seed(_) => 0,
+    }
+}
+
+fn save_state(
+    algorithm: Algorithm,
+    state: &C::XXH3_state_t,
+    entropy_pool: bool,
+) -> [u8; XXH3_SAVED_STATE_SIZE] {
+    let mode = mode(state, entropy_pool);
+    let secret = secret(state);
+    let mut w = StateWriter::new(algorithm, mode, state.totalLen);
+    for acc in &state.acc {
+        w.u64(*acc);
+    }
+    w.bytes(&state.buffer);
+    w.u32(state.bufferedSize);
+    w.u32(0);
+    w.u64(state.nbStripesSoFar as u64);
+    w.u64(secret.len() as u64);
+    w.u64(fingerprint(mode, secret));
+    w.finish()
+}
+
+/// A state saved by save_state(), checked to be usable with the secret
+/// it was saved with
+struct SavedState<'a> {
+    mode: Mode,
+    total_len: u64,
+    acc: [u64; 8],
+    buffer: &'a [u8],
+    buffered_size: u32,
+    nb_stripes_so_far: usize,
+}
+
+impl<'a> SavedState<'a> {
+    /// Parse a saved state to be restored into a hasher with the given
+    /// state; seeded states are restored after reseeding the hasher, the
+    /// entropy pool of others must match.
+    fn parse(
+        algorithm: Algorithm,
+        saved: &'a [u8],
+        state: &C::XXH3_state_t,
+        entropy_pool: bool,
+    ) -> Result<Self, Error> {
+        let mut r = StateReader::new(algorithm, saved)?;
+        let mut acc = [0; 8];
+        for a in acc.iter_mut() {
+            *a = r.u64();
+        }
+        let buffer = r.bytes(C::XXH3_INTERNALBUFFER_SIZE as usize);
+        let buffered_size = r.u32();
+        let reserved = r.u32();
+        let nb_stripes_so_far = r.u64();
+        let secret_size = r.u64();
+        let pool_fingerprint = r.u64();
+
+        let secret = match r.mode {
+            Mode::Unseeded | Mode::Seed(_) => &K_SECRET[..],
+            Mode::Entropy | Mode::EntropyAndSeed(_) if entropy_pool => secret(state),
+            _ => return Err(Error::EntropyPoolMismatch),
+        };
+        if secret_size != secret.len() as u64 || pool_fingerprint != fingerprint(r.mode, secret) {
+            return Err(Error::EntropyPoolMismatch);
+        }
+
+        // Anything else would make xxhash read out of bounds
+        let nb_stripes_per_block = (secret.len() - STRIPE_LEN) / SECRET_CONSUME_RATE;
+        if buffered_size > C::XXH3_INTERNALBUFFER_SIZE
+            || nb_stripes_so_far >= nb_stripes_per_block as u64
+            || reserved != 0
+        {
+            return Err(Error::InvalidSavedState);
+        }
+
+        Ok(SavedState {
+            mode: r.mode,
+            total_len: r.total_len,
+            acc,
+            buffer,
+            buffered_size,
+            nb_stripes_so_far: nb_stripes_so_far as usize,
+        })
+    }
+
+    /// The seed the hasher must be reset with before restoring
+    fn seed(&self) -> Option<u64> {
+        match self.mode {
+            Mode::Unseeded => Some(0),
+            Mode::Seed(seed) => Some(seed),
+            Mode::Entropy | Mode::EntropyAndSeed(_) => None,
+        }
+    }
+
+    fn restore(&self, state: &mut C::XXH3_state_t) {
+        match self.mode {
+            Mode::Entropy => {
+                state.seed = 0;
+                state.useSeed = 0;
+            }
+            Mode::EntropyAndSeed(seed) => {
+                state.seed = seed;
+                state.useSeed = 1;
+            }
+            Mode::Unseeded | Mode::Seed(_) => {}
+        }
+        state.acc = self.acc;
+        state.buffer.copy_from_slice(self.buffer);
+        state.bufferedSize = self.buffered_size;
+        state.nbStripesSoFar = self.nb_stripes_so_far;
+        state.totalLen = self.total_len;
+    }
+}
+
+impl OwnedEntropy {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        match *self {
+            #[cfg(feature = "alloc")]
+            OwnedEntropy::Boxed(ref b) => b,
+        }
+    }
+}
 
 /// xxh3 64 bit c library bindings
 ///
 /// ::default() and ::new() are equivalent; they construct the unseeded
 /// streaming variant…
-#[derive(Clone)]
+///
+/// Hashers can be sent to and shared with other threads; a hasher using
+/// a borrowed entropy pool can not outlive the pool though:
+///
+/// ```compile_fail
+/// let entropy = xxhrs::EntropyPool::with_key(b"My Custom Key!");
+/// let h = xxhrs::XXH3_64::with_entropy_ref(&entropy);
+/// std::thread::spawn(move || h.finish());
+/// ```
 pub struct XXH3_64<'a> {
     state: C::XXH3_state_t,
+    // Entropy pools not copied into the state are kept alive here;
+    // state.extSecret points into this buffer.
+    owned_entropy: Option<OwnedEntropy>,
     entropy_lifetime: PhantomData<&'a [u8]>,
+    // Whether the secret is an entropy pool rather than the default or a
+    // seed derived one; the state alone can not tell these apart
+    entropy_pool: bool,
 }
 
+impl Clone for XXH3_64<'_> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let mut r = Self {
+            state: self.state,
+            owned_entropy: self.owned_entropy.clone(),
+            entropy_lifetime: PhantomData,
+            entropy_pool: self.entropy_pool,
+        };
+        if let Some(entropy) = &r.owned_entropy {
+            r.state.extSecret = entropy.as_bytes().as_ptr();
+        }
+        r
+    }
+}
+
+// SAFETY: The only pointer in the state is extSecret, which is only ever
+// read from. It is null or points to the static default secret, to an
+// entropy pool borrowed for 'a or to entropy in owned_entropy; all of
+// which are Send and Sync themselves.
+unsafe impl Send for XXH3_64<'_> {}
+unsafe impl Sync for XXH3_64<'_> {}
+
 impl Default for XXH3_64<'_> {
     #[inline]
     fn default() -> Self {
@@ -18,19 +247,31 @@ impl Default for XXH3_64<'_> {
     }
 }
 
+/// Neither entropy pools nor seeds are printed
+impl fmt::Debug for XXH3_64<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XXH3_64")
+            .field("mode", &RedactedMode(self.mode()))
+            .field("total_len", &self.total_len())
+            .finish()
+    }
+}
+
 impl XXH3_64<'_> {
     /// One-shot hashing
     #[inline]
     pub fn hash(bytes: &[u8]) -> u64 {
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_64_short(bytes, &K_SECRET, 0);
+        }
         unsafe { C::XXH3_64bits(bytes.as_ptr() as *const c_void, bytes.len()) }
     }
 
     /// One-shot hashing with custom entropy buffer.
     ///
     /// This corresponds to XXH3_64bits_withSecret. You probably want
-    /// to use hash_with_entropy instead unless you really need to supply
-    /// custom size entropy buffers, in which case this is the function
-    /// to use.
+    /// to use hash_with_entropy instead, or hash_with_custom_entropy if
+    /// you need to supply custom size entropy buffers.
     ///
     /// # Safety
     ///
@@ -42,6 +283,9 @@ impl XXH3_64<'_> {
     #[inline]
     pub unsafe fn hash_with_entropy_buffer(entropy: &[u8], bytes: &[u8]) -> u64 {
         assert!(entropy.len() >= (C::XXH3_SECRET_SIZE_MIN) as usize);
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_64_short(bytes, entropy, 0);
+        }
         C::XXH3_64bits_withSecret(
             bytes.as_ptr() as *const c_void,
             bytes.len(),
@@ -58,12 +302,46 @@ impl XXH3_64<'_> {
         unsafe { Self::hash_with_entropy_buffer(&entropy.entropy, bytes) }
     }
 
+    /// One-shot hashing with custom size entropy pool.
+    ///
+    /// This corresponds to XXH3_64bits_withSecret.
+    #[inline]
+    #[cfg(feature = "alloc")]
+    pub fn hash_with_custom_entropy(entropy: &CustomEntropyPool, bytes: &[u8]) -> u64 {
+        unsafe { Self::hash_with_entropy_buffer(entropy.as_bytes(), bytes) }
+    }
+
     /// One-shot hashing with seed
     #[inline]
     pub fn hash_with_seed(seed: u64, bytes: &[u8]) -> u64 {
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_64_short(bytes, &K_SECRET, seed);
+        }
         unsafe { C::XXH3_64bits_withSeed(bytes.as_ptr() as *const c_void, bytes.len(), seed) }
     }
 
+    /// One-shot hashing with custom entropy buffer and seed.
+    ///
+    /// This corresponds to XXH3_64bits_withSecretandSeed. Inputs of up to
+    /// 240 bytes are hashed using just the seed, longer inputs using just
+    /// the entropy pool. This way a single, expensive to generate entropy
+    /// pool can be combined with many cheap seeds.
+    #[inline]
+    pub fn hash_with_entropy_and_seed(entropy: &EntropyPool, seed: u64, bytes: &[u8]) -> u64 {
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_64_short(bytes, &K_SECRET, seed);
+        }
+        unsafe {
+            C::XXH3_64bits_withSecretandSeed(
+                bytes.as_ptr() as *const c_void,
+                bytes.len(),
+                entropy.entropy.as_ptr() as *const c_void,
+                entropy.entropy.len(),
+                seed,
+            )
+        }
+    }
+
     /// Streaming hashing
     #[inline]
     pub fn new() -> XXH3_64<'static> {
@@ -74,13 +352,15 @@ impl XXH3_64<'_> {
             // side since they are never accessed. To avoid UB from
             // r.assume_uninit(), we initialize them to 0. The field `buffer`
             // is also not fully initialized across the FFI so we zero it out, too.
-            (*r_ptr).reserved32 = 0;
+            (*r_ptr).useSeed = 0;
             (*r_ptr).reserved64 = 0;
             (*r_ptr).buffer = [0; 256];
             C::XXH3_64bits_reset(r_ptr as *mut C::XXH3_state_t);
             XXH3_64 {
                 state: r.assume_init(),
+                owned_entropy: None,
                 entropy_lifetime: PhantomData,
+                entropy_pool: false,
             }
         }
     }
@@ -91,12 +371,13 @@ impl XXH3_64<'_> {
     ///
     /// This function is marked unsafe to discourage it's use; use with_entropy
     /// instead which copies the entropy (thus causing far fewer lifetime problems)
-    /// and uses the safer EntropyPool abstraction.
+    /// and uses the safer EntropyPool abstraction, or with_entropy_ref which
+    /// borrows the EntropyPool without copying it.
     ///
     /// # Safety
     ///
-    /// Use this function if you really want to avoid the entropy copy
-    /// or if you really need to use a custom size entropy pool.
+    /// Use this function if you really want to avoid the entropy copy;
+    /// with_custom_entropy supports custom size entropy pools safely.
     ///
     /// The entropy pool must be at least 136 bytes.
     #[inline]
@@ -108,7 +389,7 @@ impl XXH3_64<'_> {
         // r.assume_uninit(), we initialize them to 0. The field `buffer`
         // is also not fully initialized across the FFI so we zero it out, too.
         let r_ptr = r.as_mut_ptr();
-        (*r_ptr).reserved32 = 0;
+        (*r_ptr).useSeed = 0;
         (*r_ptr).reserved64 = 0;
         (*r_ptr).buffer = [0; 256];
         C::XXH3_64bits_reset_withSecret(
@@ -118,7 +399,9 @@ impl XXH3_64<'_> {
         );
         XXH3_64 {
             state: r.assume_init(),
+            owned_entropy: None,
             entropy_lifetime: PhantomData,
+            entropy_pool: true,
         }
     }
 
@@ -134,16 +417,88 @@ impl XXH3_64<'_> {
             // side since they are never accessed. To avoid UB from
             // r.assume_uninit(), we initialize them to 0. The field `buffer`
             // is also not fully initialized across the FFI so we zero it out, too.
-            (*r_ptr).reserved32 = 0;
+            (*r_ptr).useSeed = 0;
             (*r_ptr).reserved64 = 0;
             (*r_ptr).buffer = [0; 256];
             C::XXHRS_64bits_reset_withSecretCopy(
                 r_ptr as *mut C::XXH3_state_t,
                 entropy.entropy.as_ptr() as *const c_void,
+                ENTROPY_POOL_SIZE,
             );
             XXH3_64 {
                 state: r.assume_init(),
+                owned_entropy: None,
                 entropy_lifetime: PhantomData,
+                entropy_pool: true,
+            }
+        }
+    }
+
+    /// Streaming hashing with a borrowed entropy pool.
+    ///
+    /// Unlike with_entropy, this does not copy the entropy pool into the
+    /// hasher; the hasher references the pool and can not outlive it.
+    ///
+    /// ```
+    /// use xxhrs::{EntropyPool, XXH3_64};
+    ///
+    /// let pool = EntropyPool::with_key(b"My Custom Key!");
+    /// let mut h = XXH3_64::with_entropy_ref(&pool);
+    /// h.write(b"MyData");
+    /// assert_eq!(h.finish(), XXH3_64::hash_with_entropy(&pool, b"MyData"));
+    /// ```
+    #[inline]
+    pub fn with_entropy_ref<'a>(entropy: &'a EntropyPool) -> XXH3_64<'a> {
+        // SAFETY: Entropy pools are always large enough and the hasher
+        // borrows the pool for its own lifetime
+        unsafe { XXH3_64::with_entropy_buffer(&entropy.entropy) }
+    }
+
+    /// Streaming hashing with custom size entropy pool.
+    ///
+    /// Like with_entropy, this copies the entropy pool. Pools larger than
+    /// ENTROPY_POOL_SIZE do not fit into the hasher state and are copied
+    /// to the heap instead.
+    #[inline]
+    #[cfg(feature = "alloc")]
+    pub fn with_custom_entropy(entropy: &CustomEntropyPool) -> XXH3_64<'static> {
+        let entropy = entropy.as_bytes();
+        unsafe {
+            let mut r = MaybeUninit::<C::XXH3_state_t>::uninit();
+            let r_ptr = r.as_mut_ptr();
+            // SAFETY: Writes to padding fields may be optimized away on the C
+            // side since they are never accessed. To avoid UB from
+            // r.assume_uninit(), we initialize them to 0. The fields `buffer`
+            // and `customSecret` are also not fully initialized across the FFI
+            // so we zero them out, too.
+            (*r_ptr).reserved64 = 0;
+            (*r_ptr).buffer = [0; 256];
+            (*r_ptr).customSecret = [0; ENTROPY_POOL_SIZE];
+            if entropy.len() <= ENTROPY_POOL_SIZE {
+                C::XXHRS_64bits_reset_withSecretCopy(
+                    r_ptr as *mut C::XXH3_state_t,
+                    entropy.as_ptr() as *const c_void,
+                    entropy.len(),
+                );
+                XXH3_64 {
+                    state: r.assume_init(),
+                    owned_entropy: None,
+                    entropy_lifetime: PhantomData,
+                    entropy_pool: true,
+                }
+            } else {
+                let owned: Box<[u8]> = entropy.into();
+                C::XXH3_64bits_reset_withSecret(
+                    r_ptr as *mut C::XXH3_state_t,
+                    owned.as_ptr() as *const c_void,
+                    owned.len(),
+                );
+                XXH3_64 {
+                    state: r.assume_init(),
+                    owned_entropy: Some(OwnedEntropy::Boxed(owned)),
+                    entropy_lifetime: PhantomData,
+                    entropy_pool: true,
+                }
             }
         }
     }
@@ -158,21 +513,59 @@ impl XXH3_64<'_> {
             // side since they are never accessed. To avoid UB from
             // r.assume_uninit(), we initialize them to 0. The field `buffer`
             // is also not fully initialized across the FFI so we zero it out, too.
-            (*r_ptr).reserved32 = 0;
+            (*r_ptr).useSeed = 0;
             (*r_ptr).reserved64 = 0;
             (*r_ptr).buffer = [0; 256];
+            // XXH3_64bits_reset_withSeed compares these to the previous
+            // state to decide whether the secret must be regenerated.
+            (*r_ptr).seed = 0;
+            (*r_ptr).extSecret = ptr::null();
             C::XXH3_64bits_reset_withSeed(r_ptr as *mut C::XXH3_state_t, seed);
             XXH3_64 {
                 state: r.assume_init(),
+                owned_entropy: None,
+                entropy_lifetime: PhantomData,
+                entropy_pool: false,
+            }
+        }
+    }
+
+    /// Streaming hashing with custom entropy buffer and seed.
+    ///
+    /// This corresponds to XXH3_64bits_reset_withSecretandSeed, but
+    /// copies the entropy pool just like with_entropy.
+    #[inline]
+    pub fn with_entropy_and_seed(entropy: &EntropyPool, seed: u64) -> XXH3_64<'static> {
+        unsafe {
+            let mut r = MaybeUninit::<C::XXH3_state_t>::uninit();
+            let r_ptr = r.as_mut_ptr();
+            // SAFETY: Writes to padding fields may be optimized away on the C
+            // side since they are never accessed. To avoid UB from
+            // r.assume_uninit(), we initialize them to 0. The field `buffer`
+            // is also not fully initialized across the FFI so we zero it out, too.
+            (*r_ptr).reserved64 = 0;
+            (*r_ptr).buffer = [0; 256];
+            C::XXHRS_64bits_reset_withSecretandSeedCopy(
+                r_ptr as *mut C::XXH3_state_t,
+                entropy.entropy.as_ptr() as *const c_void,
+                seed,
+            );
+            XXH3_64 {
+                state: r.assume_init(),
+                owned_entropy: None,
                 entropy_lifetime: PhantomData,
+                entropy_pool: true,
             }
         }
     }
 }
 
-impl Hasher for XXH3_64<'_> {
+impl XXH3_64<'_> {
     #[inline]
-    fn write(&mut self, bytes: &[u8]) {
+    pub fn write(&mut self, bytes: &[u8]) {
+        if buffer_short_write(&mut self.state, bytes) {
+            return;
+        }
         unsafe {
             C::XXH3_64bits_update(
                 &mut self.state,
@@ -183,24 +576,186 @@ impl Hasher for XXH3_64<'_> {
     }
 
     #[inline]
-    fn finish(&self) -> u64 {
+    pub fn finish(&self) -> u64 {
         unsafe { C::XXH3_64bits_digest(&self.state) }
     }
+
+    /// Like finish(), but returns the typed hash
+    #[inline]
+    pub fn digest(&self) -> Hash64 {
+        Hash64(self.finish())
+    }
+
+    /// Discard all the data written so far, keeping the seed and
+    /// entropy pool.
+    ///
+    /// Same as `StreamingHasher::reset()`.
+    #[inline]
+    pub fn reset(&mut self) {
+        unsafe {
+            C::XXHRS_64bits_reset_keepSecret(&mut self.state);
+        }
+    }
+
+    /// Discard all the data written so far and continue with a new seed,
+    /// like with_seed() does.
+    ///
+    /// This corresponds to XXH3_64bits_reset_withSeed.
+    #[inline]
+    pub fn reset_with_seed(&mut self, seed: u64) {
+        // The C function only derives a new secret if the seed changed;
+        // customSecret might hold a copied entropy pool instead though
+        self.state.seed = 0;
+        unsafe {
+            C::XXH3_64bits_reset_withSeed(&mut self.state, seed);
+        }
+        self.owned_entropy = None;
+        self.entropy_pool = false;
+    }
+
+    /// Discard all the data written so far and continue with a copy of
+    /// the given entropy pool, like with_entropy() does.
+    #[inline]
+    pub fn reset_with_entropy(&mut self, entropy: &EntropyPool) {
+        unsafe {
+            C::XXHRS_64bits_reset_withSecretCopy(
+                &mut self.state,
+                entropy.entropy.as_ptr() as *const c_void,
+                ENTROPY_POOL_SIZE,
+            );
+        }
+        self.owned_entropy = None;
+        self.entropy_pool = true;
+    }
+
+    /// How the hasher was configured; entropy pools derived from a seed
+    /// count as `Mode::Seed`.
+    pub fn mode(&self) -> Mode {
+        mode(&self.state, self.entropy_pool)
+    }
+
+    /// Number of bytes written since the hasher was created or reset
+    pub fn total_len(&self) -> u64 {
+        self.state.totalLen
+    }
+
+    /// Save the state of the hasher, so hashing can be resumed with
+    /// restore_state(), possibly in another process or on another machine.
+    ///
+    /// The state holds the seed, the length of the input written so far,
+    /// the buffered input and the accumulators. Entropy pools are not
+    /// saved; just a fingerprint to make sure the state is restored with
+    /// the same entropy pool.
+    pub fn save_state(&self) -> [u8; XXH3_SAVED_STATE_SIZE] {
+        save_state(Algorithm::XXH3_64, &self.state, self.entropy_pool)
+    }
+
+    /// Continue hashing from a state saved with save_state(), replacing
+    /// the data written so far.
+    ///
+    /// Unseeded and seeded states can be restored into any XXH3_64, which
+    /// is reseeded. States saved by a hasher with an entropy pool can only
+    /// be restored into a hasher using the same entropy pool; otherwise
+    /// this fails with `Error::EntropyPoolMismatch`. Fails with
+    /// `Error::InvalidSavedState` if the state was not saved by an XXH3_64
+    /// hasher or is corrupted. The hasher is unchanged on failure.
+    ///
+    /// ```
+    /// use xxhrs::{EntropyPool, XXH3_64};
+    ///
+    /// let pool = EntropyPool::with_key(b"My Custom Key!");
+    /// let mut h = XXH3_64::with_entropy(&pool);
+    /// h.write(b"MyFirst");
+    /// let saved = h.save_state();
+    ///
+    /// let mut h = XXH3_64::with_entropy(&pool);
+    /// h.restore_state(&saved).unwrap();
+    /// h.write(b"Data");
+    /// assert_eq!(h.finish(), XXH3_64::hash_with_entropy(&pool, b"MyFirstData"));
+    /// ```
+    pub fn restore_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        let saved = SavedState::parse(Algorithm::XXH3_64, state, &self.state, self.entropy_pool)?;
+        if let Some(seed) = saved.seed() {
+            self.reset_with_seed(seed);
+        }
+        saved.restore(&mut self.state);
+        Ok(())
+    }
+}
+
+impl Hasher for XXH3_64<'_> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        XXH3_64::write(self, bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        XXH3_64::finish(self)
+    }
+}
+
+impl StreamingHasher for XXH3_64<'_> {
+    type Output = u64;
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        XXH3_64::write(self, bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        XXH3_64::finish(self)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        XXH3_64::reset(self)
+    }
 }
 
 /// xxh3 64 bit c library bindings
 ///
 /// Streaming mode is used just like the `Hasher` trait, but does
-/// not implement the trait because this returns u128, hasher requires u64
+/// not implement the trait because this returns u128, hasher requires u64.
+/// Implements `StreamingHasher` instead.
 ///
 /// ::default() and ::new() are equivalent; they construct the unseeded
 /// streaming variant…
-#[derive(Clone)]
 pub struct XXH3_128<'a> {
     state: C::XXH3_state_t,
+    // Entropy pools not copied into the state are kept alive here;
+    // state.extSecret points into this buffer.
+    owned_entropy: Option<OwnedEntropy>,
     entropy_lifetime: PhantomData<&'a [u8]>,
+    // Whether the secret is an entropy pool rather than the default or a
+    // seed derived one; the state alone can not tell these apart
+    entropy_pool: bool,
+}
+
+impl Clone for XXH3_128<'_> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let mut r = Self {
+            state: self.state,
+            owned_entropy: self.owned_entropy.clone(),
+            entropy_lifetime: PhantomData,
+            entropy_pool: self.entropy_pool,
+        };
+        if let Some(entropy) = &r.owned_entropy {
+            r.state.extSecret = entropy.as_bytes().as_ptr();
+        }
+        r
+    }
 }
 
+// SAFETY: The only pointer in the state is extSecret, which is only ever
+// read from. It is null or points to the static default secret, to an
+// entropy pool borrowed for 'a or to entropy in owned_entropy; all of
+// which are Send and Sync themselves.
+unsafe impl Send for XXH3_128<'_> {}
+unsafe impl Sync for XXH3_128<'_> {}
+
 impl Default for XXH3_128<'_> {
     #[inline]
     fn default() -> Self {
@@ -208,8 +763,18 @@ impl Default for XXH3_128<'_> {
     }
 }
 
+/// Neither entropy pools nor seeds are printed
+impl fmt::Debug for XXH3_128<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XXH3_128")
+            .field("mode", &RedactedMode(self.mode()))
+            .field("total_len", &self.total_len())
+            .finish()
+    }
+}
+
 #[inline]
-fn xxh128_to_u128(val: C::XXH128_hash_t) -> u128 {
+pub(crate) fn xxh128_to_u128(val: C::XXH128_hash_t) -> u128 {
     (val.low64 as u128) | (val.high64 as u128) << 64
 }
 
@@ -217,6 +782,9 @@ impl XXH3_128<'_> {
     /// One-shot hashing
     #[inline]
     pub fn hash(bytes: &[u8]) -> u128 {
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_128_short(bytes, &K_SECRET, 0);
+        }
         let r = unsafe { C::XXH3_128bits(bytes.as_ptr() as *const c_void, bytes.len()) };
         xxh128_to_u128(r)
     }
@@ -224,9 +792,8 @@ impl XXH3_128<'_> {
     /// One-shot hashing with custom entropy buffer.
     ///
     /// This corresponds to XXH3_64bits_withSecret. You probably want
-    /// to use hash_with_entropy instead unless you really need to supply
-    /// custom size entropy buffers, in which case this is the function
-    /// to use.
+    /// to use hash_with_entropy instead, or hash_with_custom_entropy if
+    /// you need to supply custom size entropy buffers.
     ///
     /// # Safety
     ///
@@ -238,6 +805,9 @@ impl XXH3_128<'_> {
     #[inline]
     pub unsafe fn hash_with_entropy_buffer(entropy: &[u8], bytes: &[u8]) -> u128 {
         assert!(entropy.len() >= (C::XXH3_SECRET_SIZE_MIN) as usize);
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_128_short(bytes, entropy, 0);
+        }
         let r = C::XXH3_128bits_withSecret(
             bytes.as_ptr() as *const c_void,
             bytes.len(),
@@ -255,14 +825,49 @@ impl XXH3_128<'_> {
         unsafe { Self::hash_with_entropy_buffer(&entropy.entropy, bytes) }
     }
 
+    /// One-shot hashing with custom size entropy pool.
+    ///
+    /// This corresponds to XXH3_128bits_withSecret.
+    #[inline]
+    #[cfg(feature = "alloc")]
+    pub fn hash_with_custom_entropy(entropy: &CustomEntropyPool, bytes: &[u8]) -> u128 {
+        unsafe { Self::hash_with_entropy_buffer(entropy.as_bytes(), bytes) }
+    }
+
     /// One-shot hashing with seed
     #[inline]
     pub fn hash_with_seed(seed: u64, bytes: &[u8]) -> u128 {
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_128_short(bytes, &K_SECRET, seed);
+        }
         let r =
             unsafe { C::XXH3_128bits_withSeed(bytes.as_ptr() as *const c_void, bytes.len(), seed) };
         xxh128_to_u128(r)
     }
 
+    /// One-shot hashing with custom entropy buffer and seed.
+    ///
+    /// This corresponds to XXH3_128bits_withSecretandSeed. Inputs of up to
+    /// 240 bytes are hashed using just the seed, longer inputs using just
+    /// the entropy pool. This way a single, expensive to generate entropy
+    /// pool can be combined with many cheap seeds.
+    #[inline]
+    pub fn hash_with_entropy_and_seed(entropy: &EntropyPool, seed: u64, bytes: &[u8]) -> u128 {
+        if bytes.len() <= MIDSIZE_MAX {
+            return xxh3_128_short(bytes, &K_SECRET, seed);
+        }
+        let r = unsafe {
+            C::XXH3_128bits_withSecretandSeed(
+                bytes.as_ptr() as *const c_void,
+                bytes.len(),
+                entropy.entropy.as_ptr() as *const c_void,
+                entropy.entropy.len(),
+                seed,
+            )
+        };
+        xxh128_to_u128(r)
+    }
+
     /// Streaming hashing
     #[inline]
     pub fn new() -> XXH3_128<'static> {
@@ -273,13 +878,15 @@ impl XXH3_128<'_> {
             // side since they are never accessed. To avoid UB from
             // r.assume_uninit(), we initialize them to 0. The field `buffer`
             // is also not fully initialized across the FFI so we zero it out, too.
-            (*r_ptr).reserved32 = 0;
+            (*r_ptr).useSeed = 0;
             (*r_ptr).reserved64 = 0;
             (*r_ptr).buffer = [0; 256];
             C::XXH3_128bits_reset(r_ptr as *mut C::XXH3_state_t);
             XXH3_128 {
                 state: r.assume_init(),
+                owned_entropy: None,
                 entropy_lifetime: PhantomData,
+                entropy_pool: false,
             }
         }
     }
@@ -290,12 +897,13 @@ impl XXH3_128<'_> {
     ///
     /// This function is marked unsafe to discourage it's use; use with_entropy
     /// instead which copies the entropy (thus causing far fewer lifetime problems)
-    /// and uses the safer EntropyPool abstraction.
+    /// and uses the safer EntropyPool abstraction, or with_entropy_ref which
+    /// borrows the EntropyPool without copying it.
     ///
     /// # Safety
     ///
-    /// Use this function if you really want to avoid the entropy copy
-    /// or if you really need to use a custom size entropy pool.
+    /// Use this function if you really want to avoid the entropy copy;
+    /// with_custom_entropy supports custom size entropy pools safely.
     ///
     /// The entropy pool must be at least 136 bytes.
     #[inline]
@@ -307,7 +915,7 @@ impl XXH3_128<'_> {
         // side since they are never accessed. To avoid UB from
         // r.assume_uninit(), we initialize them to 0. The field `buffer`
         // is also not fully initialized across the FFI so we zero it out, too.
-        (*r_ptr).reserved32 = 0;
+        (*r_ptr).useSeed = 0;
         (*r_ptr).reserved64 = 0;
         (*r_ptr).buffer = [0; 256];
         C::XXH3_128bits_reset_withSecret(
@@ -317,7 +925,9 @@ impl XXH3_128<'_> {
         );
         XXH3_128 {
             state: r.assume_init(),
+            owned_entropy: None,
             entropy_lifetime: PhantomData,
+            entropy_pool: true,
         }
     }
 
@@ -333,16 +943,88 @@ impl XXH3_128<'_> {
             // side since they are never accessed. To avoid UB from
             // r.assume_uninit(), we initialize them to 0. The field `buffer`
             // is also not fully initialized across the FFI so we zero it out, too.
-            (*r_ptr).reserved32 = 0;
+            (*r_ptr).useSeed = 0;
             (*r_ptr).reserved64 = 0;
             (*r_ptr).buffer = [0; 256];
             C::XXHRS_128bits_reset_withSecretCopy(
                 r_ptr as *mut C::XXH3_state_t,
                 entropy.entropy.as_ptr() as *const c_void,
+                ENTROPY_POOL_SIZE,
             );
             XXH3_128 {
                 state: r.assume_init(),
+                owned_entropy: None,
                 entropy_lifetime: PhantomData,
+                entropy_pool: true,
+            }
+        }
+    }
+
+    /// Streaming hashing with a borrowed entropy pool.
+    ///
+    /// Unlike with_entropy, this does not copy the entropy pool into the
+    /// hasher; the hasher references the pool and can not outlive it.
+    ///
+    /// ```
+    /// use xxhrs::{EntropyPool, XXH3_128};
+    ///
+    /// let pool = EntropyPool::with_key(b"My Custom Key!");
+    /// let mut h = XXH3_128::with_entropy_ref(&pool);
+    /// h.write(b"MyData");
+    /// assert_eq!(h.finish(), XXH3_128::hash_with_entropy(&pool, b"MyData"));
+    /// ```
+    #[inline]
+    pub fn with_entropy_ref<'a>(entropy: &'a EntropyPool) -> XXH3_128<'a> {
+        // SAFETY: Entropy pools are always large enough and the hasher
+        // borrows the pool for its own lifetime
+        unsafe { XXH3_128::with_entropy_buffer(&entropy.entropy) }
+    }
+
+    /// Streaming hashing with custom size entropy pool.
+    ///
+    /// Like with_entropy, this copies the entropy pool. Pools larger than
+    /// ENTROPY_POOL_SIZE do not fit into the hasher state and are copied
+    /// to the heap instead.
+    #[inline]
+    #[cfg(feature = "alloc")]
+    pub fn with_custom_entropy(entropy: &CustomEntropyPool) -> XXH3_128<'static> {
+        let entropy = entropy.as_bytes();
+        unsafe {
+            let mut r = MaybeUninit::<C::XXH3_state_t>::uninit();
+            let r_ptr = r.as_mut_ptr();
+            // SAFETY: Writes to padding fields may be optimized away on the C
+            // side since they are never accessed. To avoid UB from
+            // r.assume_uninit(), we initialize them to 0. The fields `buffer`
+            // and `customSecret` are also not fully initialized across the FFI
+            // so we zero them out, too.
+            (*r_ptr).reserved64 = 0;
+            (*r_ptr).buffer = [0; 256];
+            (*r_ptr).customSecret = [0; ENTROPY_POOL_SIZE];
+            if entropy.len() <= ENTROPY_POOL_SIZE {
+                C::XXHRS_128bits_reset_withSecretCopy(
+                    r_ptr as *mut C::XXH3_state_t,
+                    entropy.as_ptr() as *const c_void,
+                    entropy.len(),
+                );
+                XXH3_128 {
+                    state: r.assume_init(),
+                    owned_entropy: None,
+                    entropy_lifetime: PhantomData,
+                    entropy_pool: true,
+                }
+            } else {
+                let owned: Box<[u8]> = entropy.into();
+                C::XXH3_128bits_reset_withSecret(
+                    r_ptr as *mut C::XXH3_state_t,
+                    owned.as_ptr() as *const c_void,
+                    owned.len(),
+                );
+                XXH3_128 {
+                    state: r.assume_init(),
+                    owned_entropy: Some(OwnedEntropy::Boxed(owned)),
+                    entropy_lifetime: PhantomData,
+                    entropy_pool: true,
+                }
             }
         }
     }
@@ -357,19 +1039,57 @@ impl XXH3_128<'_> {
             // side since they are never accessed. To avoid UB from
             // r.assume_uninit(), we initialize them to 0. The field `buffer`
             // is also not fully initialized across the FFI so we zero it out, too.
-            (*r_ptr).reserved32 = 0;
+            (*r_ptr).useSeed = 0;
             (*r_ptr).reserved64 = 0;
             (*r_ptr).buffer = [0; 256];
+            // XXH3_128bits_reset_withSeed compares these to the previous
+            // state to decide whether the secret must be regenerated.
+            (*r_ptr).seed = 0;
+            (*r_ptr).extSecret = ptr::null();
             C::XXH3_128bits_reset_withSeed(r_ptr as *mut C::XXH3_state_t, seed);
             XXH3_128 {
                 state: r.assume_init(),
+                owned_entropy: None,
+                entropy_lifetime: PhantomData,
+                entropy_pool: false,
+            }
+        }
+    }
+
+    /// Streaming hashing with custom entropy buffer and seed.
+    ///
+    /// This corresponds to XXH3_128bits_reset_withSecretandSeed, but
+    /// copies the entropy pool just like with_entropy.
+    #[inline]
+    pub fn with_entropy_and_seed(entropy: &EntropyPool, seed: u64) -> XXH3_128<'static> {
+        unsafe {
+            let mut r = MaybeUninit::<C::XXH3_state_t>::uninit();
+            let r_ptr = r.as_mut_ptr();
+            // SAFETY: Writes to padding fields may be optimized away on the C
+            // side since they are never accessed. To avoid UB from
+            // r.assume_uninit(), we initialize them to 0. The field `buffer`
+            // is also not fully initialized across the FFI so we zero it out, too.
+            (*r_ptr).reserved64 = 0;
+            (*r_ptr).buffer = [0; 256];
+            C::XXHRS_128bits_reset_withSecretandSeedCopy(
+                r_ptr as *mut C::XXH3_state_t,
+                entropy.entropy.as_ptr() as *const c_void,
+                seed,
+            );
+            XXH3_128 {
+                state: r.assume_init(),
+                owned_entropy: None,
                 entropy_lifetime: PhantomData,
+                entropy_pool: true,
             }
         }
     }
 
     #[inline]
     pub fn write(&mut self, bytes: &[u8]) {
+        if buffer_short_write(&mut self.state, bytes) {
+            return;
+        }
         unsafe {
             C::XXH3_128bits_update(
                 &mut self.state,
@@ -381,7 +1101,136 @@ impl XXH3_128<'_> {
 
     #[inline]
     pub fn finish(&self) -> u128 {
+        // XXH3_128bits_digest of xxhash 0.8.2 checks the seed instead of
+        // useSeed, so with_entropy_and_seed(…, 0) would hash short inputs
+        // with the entropy pool, unlike hash_with_entropy_and_seed()
+        let len = self.state.totalLen;
+        if self.state.useSeed != 0 && len <= MIDSIZE_MAX as u64 {
+            let input = &self.state.buffer[..len as usize];
+            return xxh3_128_short(input, &K_SECRET, self.state.seed);
+        }
         let r = unsafe { C::XXH3_128bits_digest(&self.state) };
         xxh128_to_u128(r)
     }
+
+    /// Like finish(), but returns the typed hash
+    #[inline]
+    pub fn digest(&self) -> Hash128 {
+        Hash128(self.finish())
+    }
+
+    /// Discard all the data written so far, keeping the seed and
+    /// entropy pool.
+    ///
+    /// Same as `StreamingHasher::reset()`.
+    #[inline]
+    pub fn reset(&mut self) {
+        unsafe {
+            C::XXHRS_128bits_reset_keepSecret(&mut self.state);
+        }
+    }
+
+    /// Discard all the data written so far and continue with a new seed,
+    /// like with_seed() does.
+    ///
+    /// This corresponds to XXH3_128bits_reset_withSeed.
+    #[inline]
+    pub fn reset_with_seed(&mut self, seed: u64) {
+        // The C function only derives a new secret if the seed changed;
+        // customSecret might hold a copied entropy pool instead though
+        self.state.seed = 0;
+        unsafe {
+            C::XXH3_128bits_reset_withSeed(&mut self.state, seed);
+        }
+        self.owned_entropy = None;
+        self.entropy_pool = false;
+    }
+
+    /// Discard all the data written so far and continue with a copy of
+    /// the given entropy pool, like with_entropy() does.
+    #[inline]
+    pub fn reset_with_entropy(&mut self, entropy: &EntropyPool) {
+        unsafe {
+            C::XXHRS_128bits_reset_withSecretCopy(
+                &mut self.state,
+                entropy.entropy.as_ptr() as *const c_void,
+                ENTROPY_POOL_SIZE,
+            );
+        }
+        self.owned_entropy = None;
+        self.entropy_pool = true;
+    }
+
+    /// How the hasher was configured; entropy pools derived from a seed
+    /// count as `Mode::Seed`.
+    pub fn mode(&self) -> Mode {
+        mode(&self.state, self.entropy_pool)
+    }
+
+    /// Number of bytes written since the hasher was created or reset
+    pub fn total_len(&self) -> u64 {
+        self.state.totalLen
+    }
+
+    /// Save the state of the hasher, so hashing can be resumed with
+    /// restore_state(), possibly in another process or on another machine.
+    ///
+    /// The state holds the seed, the length of the input written so far,
+    /// the buffered input and the accumulators. Entropy pools are not
+    /// saved; just a fingerprint to make sure the state is restored with
+    /// the same entropy pool.
+    pub fn save_state(&self) -> [u8; XXH3_SAVED_STATE_SIZE] {
+        save_state(Algorithm::XXH3_128, &self.state, self.entropy_pool)
+    }
+
+    /// Continue hashing from a state saved with save_state(), replacing
+    /// the data written so far.
+    ///
+    /// Unseeded and seeded states can be restored into any XXH3_128, which
+    /// is reseeded. States saved by a hasher with an entropy pool can only
+    /// be restored into a hasher using the same entropy pool; otherwise
+    /// this fails with `Error::EntropyPoolMismatch`. Fails with
+    /// `Error::InvalidSavedState` if the state was not saved by an XXH3_128
+    /// hasher or is corrupted. The hasher is unchanged on failure.
+    ///
+    /// ```
+    /// use xxhrs::{EntropyPool, XXH3_128};
+    ///
+    /// let pool = EntropyPool::with_key(b"My Custom Key!");
+    /// let mut h = XXH3_128::with_entropy(&pool);
+    /// h.write(b"MyFirst");
+    /// let saved = h.save_state();
+    ///
+    /// let mut h = XXH3_128::with_entropy(&pool);
+    /// h.restore_state(&saved).unwrap();
+    /// h.write(b"Data");
+    /// assert_eq!(h.finish(), XXH3_128::hash_with_entropy(&pool, b"MyFirstData"));
+    /// ```
+    pub fn restore_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        let saved = SavedState::parse(Algorithm::XXH3_128, state, &self.state, self.entropy_pool)?;
+        if let Some(seed) = saved.seed() {
+            self.reset_with_seed(seed);
+        }
+        saved.restore(&mut self.state);
+        Ok(())
+    }
+}
+
+impl StreamingHasher for XXH3_128<'_> {
+    type Output = u128;
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        XXH3_128::write(self, bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u128 {
+        XXH3_128::finish(self)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        XXH3_128::reset(self)
+    }
 }
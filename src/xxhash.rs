@@ -1,17 +1,30 @@
-use std::default::Default;
-use std::hash::Hasher;
-use std::mem::MaybeUninit;
-use std::os::raw::c_void;
+use core::convert::TryFrom;
+use core::default::Default;
+use core::ffi::c_void;
+use core::fmt;
+use core::hash::Hasher;
+use core::mem::MaybeUninit;
+use core::ptr;
 
-use crate::C;
+use crate::{
+    digest::{Hash32, Hash64},
+    error::Error,
+    hasher::{Mode, RedactedMode, StreamingHasher},
+    savedstate::{
+        Algorithm, StateReader, StateWriter, XXH32_SAVED_STATE_SIZE, XXH64_SAVED_STATE_SIZE,
+    },
+    C,
+};
 
 /// xxhash 32 bit c library bindings
 ///
 /// Streaming mode is used just like the `Hasher` trait, but does
-/// not implement the trait because this returns u32, hasher requires u64
+/// not implement the trait because this returns u32, hasher requires u64.
+/// Implements `StreamingHasher` instead.
 #[derive(Clone)]
 pub struct XXH32 {
     state: C::XXH32_state_t,
+    seed: u32,
 }
 
 impl Default for XXH32 {
@@ -21,6 +34,16 @@ impl Default for XXH32 {
     }
 }
 
+/// Seeds are not printed
+impl fmt::Debug for XXH32 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XXH32")
+            .field("mode", &RedactedMode(self.mode()))
+            .field("total_len", &self.total_len())
+            .finish()
+    }
+}
+
 impl XXH32 {
     /// One-shot hashing
     #[inline]
@@ -53,6 +76,7 @@ impl XXH32 {
             C::XXH32_reset(r_ptr as *mut C::XXH32_state_t, seed);
             XXH32 {
                 state: r.assume_init(),
+                seed,
             }
         }
     }
@@ -72,12 +96,128 @@ impl XXH32 {
     pub fn finish(&self) -> u32 {
         unsafe { C::XXH32_digest(&self.state) }
     }
+
+    /// Like finish(), but returns the typed hash
+    #[inline]
+    pub fn digest(&self) -> Hash32 {
+        Hash32(self.finish())
+    }
+
+    /// Discard all the data written so far, keeping the seed.
+    ///
+    /// Same as `StreamingHasher::reset()`.
+    #[inline]
+    pub fn reset(&mut self) {
+        unsafe {
+            C::XXH32_reset(&mut self.state, self.seed);
+        }
+    }
+
+    /// Discard all the data written so far and continue with a new seed
+    #[inline]
+    pub fn reset_with_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.reset();
+    }
+
+    /// The seed in use; a seed of zero is reported as `Mode::Unseeded`
+    pub fn mode(&self) -> Mode {
+        match self.seed {
+            0 => Mode::Unseeded,
+            seed => Mode::Seed(seed as u64),
+        }
+    }
+
+    /// Number of bytes written since the hasher was created or reset,
+    /// modulo 2^32; XXH32 does not keep track of more.
+    pub fn total_len(&self) -> u64 {
+        self.state.total_len_32 as u64
+    }
+
+    /// Save the state of the hasher, so hashing can be resumed with
+    /// restore_state(), possibly in another process or on another machine.
+    ///
+    /// The state holds the seed, the length of the input written so far
+    /// (modulo 2^32, like XXH32 tracks it) and the buffered input.
+    pub fn save_state(&self) -> [u8; XXH32_SAVED_STATE_SIZE] {
+        let s = &self.state;
+        let mut w = StateWriter::new(Algorithm::XXH32, self.mode(), s.total_len_32 as u64);
+        for v in &s.v {
+            w.u32(*v);
+        }
+        // mem32 holds raw input bytes
+        for m in &s.mem32 {
+            w.bytes(&m.to_ne_bytes());
+        }
+        w.u32(s.memsize);
+        w.u32(s.large_len);
+        w.finish()
+    }
+
+    /// Continue hashing from a state saved with save_state(), replacing
+    /// the data written so far and the seed.
+    ///
+    /// Fails with `Error::InvalidSavedState` if the state was not saved by
+    /// an XXH32 hasher or is corrupted.
+    pub fn restore_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        let mut r = StateReader::new(Algorithm::XXH32, state)?;
+        let seed = match r.mode {
+            Mode::Unseeded => 0,
+            Mode::Seed(seed) => u32::try_from(seed).map_err(|_| Error::InvalidSavedState)?,
+            _ => return Err(Error::InvalidSavedState),
+        };
+        let total_len = u32::try_from(r.total_len).map_err(|_| Error::InvalidSavedState)?;
+        let mut v = [0; 4];
+        for x in v.iter_mut() {
+            *x = r.u32();
+        }
+        let mut mem32 = [0; 4];
+        for m in mem32.iter_mut() {
+            let mut raw = [0; 4];
+            raw.copy_from_slice(r.bytes(4));
+            *m = u32::from_ne_bytes(raw);
+        }
+        let memsize = r.u32();
+        let large_len = r.u32();
+        if memsize != total_len % 16 || large_len > 1 || (total_len >= 16 && large_len == 0) {
+            return Err(Error::InvalidSavedState);
+        }
+
+        self.seed = seed;
+        let s = &mut self.state;
+        s.total_len_32 = total_len;
+        s.large_len = large_len;
+        s.v = v;
+        s.mem32 = mem32;
+        s.memsize = memsize;
+        Ok(())
+    }
+}
+
+impl StreamingHasher for XXH32 {
+    type Output = u32;
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        XXH32::write(self, bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u32 {
+        XXH32::finish(self)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        XXH32::reset(self)
+    }
 }
 
 /// xxhash 32 bit c library bindings
 #[derive(Clone)]
 pub struct XXH64 {
     state: C::XXH64_state_t,
+    seed: u64,
 }
 
 impl Default for XXH64 {
@@ -87,6 +227,16 @@ impl Default for XXH64 {
     }
 }
 
+/// Seeds are not printed
+impl fmt::Debug for XXH64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XXH64")
+            .field("mode", &RedactedMode(self.mode()))
+            .field("total_len", &self.total_len())
+            .finish()
+    }
+}
+
 impl XXH64 {
     /// One-shot hashing
     #[inline]
@@ -120,14 +270,26 @@ impl XXH64 {
             C::XXH64_reset(r_ptr as *mut C::XXH64_state_t, seed);
             XXH64 {
                 state: r.assume_init(),
+                seed,
             }
         }
     }
-}
 
-impl Hasher for XXH64 {
     #[inline]
-    fn write(&mut self, bytes: &[u8]) {
+    pub fn write(&mut self, bytes: &[u8]) {
+        // Inputs that still fit into the internal buffer are just appended,
+        // exactly like XXH64_update would; this saves the FFI call when
+        // hashing small keys.
+        let memsize = self.state.memsize as usize;
+        if memsize + bytes.len() < 32 {
+            unsafe {
+                let mem = self.state.mem64.as_mut_ptr() as *mut u8;
+                ptr::copy_nonoverlapping(bytes.as_ptr(), mem.add(memsize), bytes.len());
+            }
+            self.state.memsize += bytes.len() as u32;
+            self.state.total_len += bytes.len() as u64;
+            return;
+        }
         unsafe {
             C::XXH64_update(
                 &mut self.state,
@@ -138,7 +300,131 @@ impl Hasher for XXH64 {
     }
 
     #[inline]
-    fn finish(&self) -> u64 {
+    pub fn finish(&self) -> u64 {
         unsafe { C::XXH64_digest(&self.state) }
     }
+
+    /// Like finish(), but returns the typed hash
+    #[inline]
+    pub fn digest(&self) -> Hash64 {
+        Hash64(self.finish())
+    }
+
+    /// Discard all the data written so far, keeping the seed.
+    ///
+    /// Same as `StreamingHasher::reset()`.
+    #[inline]
+    pub fn reset(&mut self) {
+        unsafe {
+            C::XXH64_reset(&mut self.state, self.seed);
+        }
+    }
+
+    /// Discard all the data written so far and continue with a new seed
+    #[inline]
+    pub fn reset_with_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.reset();
+    }
+
+    /// The seed in use; a seed of zero is reported as `Mode::Unseeded`
+    pub fn mode(&self) -> Mode {
+        match self.seed {
+            0 => Mode::Unseeded,
+            seed => Mode::Seed(seed),
+        }
+    }
+
+    /// Number of bytes written since the hasher was created or reset
+    pub fn total_len(&self) -> u64 {
+        self.state.total_len
+    }
+
+    /// Save the state of the hasher, so hashing can be resumed with
+    /// restore_state(), possibly in another process or on another machine.
+    ///
+    /// The state holds the seed, the length of the input written so far
+    /// and the buffered input.
+    pub fn save_state(&self) -> [u8; XXH64_SAVED_STATE_SIZE] {
+        let s = &self.state;
+        let mut w = StateWriter::new(Algorithm::XXH64, self.mode(), s.total_len);
+        for v in &s.v {
+            w.u64(*v);
+        }
+        // mem64 holds raw input bytes
+        for m in &s.mem64 {
+            w.bytes(&m.to_ne_bytes());
+        }
+        w.u32(s.memsize);
+        w.u32(0);
+        w.finish()
+    }
+
+    /// Continue hashing from a state saved with save_state(), replacing
+    /// the data written so far and the seed.
+    ///
+    /// Fails with `Error::InvalidSavedState` if the state was not saved by
+    /// an XXH64 hasher or is corrupted.
+    pub fn restore_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        let mut r = StateReader::new(Algorithm::XXH64, state)?;
+        let seed = match r.mode {
+            Mode::Unseeded => 0,
+            Mode::Seed(seed) => seed,
+            _ => return Err(Error::InvalidSavedState),
+        };
+        let total_len = r.total_len;
+        let mut v = [0; 4];
+        for x in v.iter_mut() {
+            *x = r.u64();
+        }
+        let mut mem64 = [0; 4];
+        for m in mem64.iter_mut() {
+            let mut raw = [0; 8];
+            raw.copy_from_slice(r.bytes(8));
+            *m = u64::from_ne_bytes(raw);
+        }
+        let memsize = r.u32();
+        if memsize as u64 != total_len % 32 || r.u32() != 0 {
+            return Err(Error::InvalidSavedState);
+        }
+
+        self.seed = seed;
+        let s = &mut self.state;
+        s.total_len = total_len;
+        s.v = v;
+        s.mem64 = mem64;
+        s.memsize = memsize;
+        Ok(())
+    }
+}
+
+impl Hasher for XXH64 {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        XXH64::write(self, bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        XXH64::finish(self)
+    }
+}
+
+impl StreamingHasher for XXH64 {
+    type Output = u64;
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        XXH64::write(self, bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        XXH64::finish(self)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        XXH64::reset(self)
+    }
 }
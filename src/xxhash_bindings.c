@@ -1,15 +1,276 @@
 #define XXH_IMPLEMENTATION
 #include <stdint.h>
+
+/* On x86, compile the SSE2, AVX2 and AVX512 kernels alongside the scalar
+ * one using target attributes, like xxh_x86dispatch.c does upstream; the
+ * kernel used is chosen at runtime based on cpuid. Unless a kernel was
+ * explicitly selected by setting XXH_VECTOR. */
+#if (defined(__x86_64__) || defined(__i386__) || defined(_M_X64) || defined(_M_IX86)) \
+    && !defined(XXH_VECTOR)
+#  define XXHRS_X86DISPATCH 1
+#  define XXH_X86DISPATCH
+#  define XXH_DISPATCH_AVX2 1
+#  define XXH_DISPATCH_AVX512 1
+#  if defined(__GNUC__) || defined(__clang__)
+#    define XXH_TARGET_SSE2 __attribute__((__target__("sse2")))
+#    define XXH_TARGET_AVX2 __attribute__((__target__("avx2")))
+#    define XXH_TARGET_AVX512 __attribute__((__target__("avx512f")))
+#  endif
+#  include <immintrin.h>
+#else
+#  define XXHRS_X86DISPATCH 0
+#endif
+
 #include "xxhash_bindings.h"
 
-void XXHRS_64bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret)  {
-  XXH3_64bits_reset_internal(statePtr, 0, secret, XXH_SECRET_DEFAULT_SIZE);
-  memcpy(statePtr->customSecret, secret, XXH_SECRET_DEFAULT_SIZE);
+/* secretSize must be between XXH3_SECRET_SIZE_MIN and XXH3_SECRET_DEFAULT_SIZE */
+void XXHRS_64bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret, size_t secretSize)  {
+  XXH_ASSERT(secretSize >= XXH3_SECRET_SIZE_MIN);
+  XXH_ASSERT(secretSize <= XXH3_SECRET_DEFAULT_SIZE);
+  XXH3_reset_internal(statePtr, 0, secret, secretSize);
+  memcpy(statePtr->customSecret, secret, secretSize);
   statePtr->extSecret = NULL;
 }
 
-void XXHRS_128bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret)  {
-  XXH3_128bits_reset_internal(statePtr, 0, secret, XXH_SECRET_DEFAULT_SIZE);
-  memcpy(statePtr->customSecret, secret, XXH_SECRET_DEFAULT_SIZE);
-  statePtr->extSecret = NULL;
+void XXHRS_128bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret, size_t secretSize)  {
+  XXHRS_64bits_reset_withSecretCopy(statePtr, secret, secretSize);
+}
+
+void XXHRS_64bits_reset_withSecretandSeedCopy(XXH3_state_t* statePtr, const void* secret, XXH64_hash_t seed)  {
+  XXHRS_64bits_reset_withSecretCopy(statePtr, secret, XXH_SECRET_DEFAULT_SIZE);
+  statePtr->seed = seed;
+  statePtr->useSeed = 1; /* always, even if seed==0 */
+}
+
+void XXHRS_128bits_reset_withSecretandSeedCopy(XXH3_state_t* statePtr, const void* secret, XXH64_hash_t seed)  {
+  XXHRS_64bits_reset_withSecretandSeedCopy(statePtr, secret, seed);
+}
+
+void XXHRS_64bits_reset_keepSecret(XXH3_state_t* statePtr) {
+  XXH32_hash_t const useSeed = statePtr->useSeed;
+  XXH3_reset_internal(statePtr, statePtr->seed, statePtr->extSecret,
+                      statePtr->secretLimit + XXH_STRIPE_LEN);
+  statePtr->useSeed = useSeed;
+}
+
+void XXHRS_128bits_reset_keepSecret(XXH3_state_t* statePtr) {
+  XXHRS_64bits_reset_keepSecret(statePtr);
+}
+
+/* Build configuration */
+
+XXHRS_config_t XXHRS_config(void) {
+  XXHRS_config_t r;
+  r.vector = XXH_VECTOR;
+  r.dispatch = XXHRS_X86DISPATCH;
+  r.sizeOpt = XXH_SIZE_OPT;
+#ifdef XXH_FORCE_MEMORY_ACCESS
+  r.forceMemoryAccess = XXH_FORCE_MEMORY_ACCESS;
+#else
+  r.forceMemoryAccess = 0;
+#endif
+  return r;
+}
+
+/* Runtime dispatch */
+
+#if XXHRS_X86DISPATCH
+
+#if defined(_MSC_VER) && !defined(__clang__)
+#  include <intrin.h>
+static int XXHRS_cpuid(unsigned leaf, unsigned sub, unsigned r[4]) {
+  int max[4], regs[4];
+  __cpuid(max, 0);
+  if ((unsigned)max[0] < leaf) return 0;
+  __cpuidex(regs, (int)leaf, (int)sub);
+  memcpy(r, regs, sizeof(regs));
+  return 1;
+}
+static xxh_u64 XXHRS_xgetbv(void) {
+  return _xgetbv(0);
+}
+#  define XXHRS_LOAD(v) (v)
+#  define XXHRS_STORE(v, x) ((v) = (x))
+#else
+#  include <cpuid.h>
+static int XXHRS_cpuid(unsigned leaf, unsigned sub, unsigned r[4]) {
+  if (__get_cpuid_max(0, NULL) < leaf) return 0;
+  __cpuid_count(leaf, sub, r[0], r[1], r[2], r[3]);
+  return 1;
+}
+static xxh_u64 XXHRS_xgetbv(void) {
+  xxh_u32 lo, hi;
+  __asm__ __volatile__(".byte 0x0f, 0x01, 0xd0" : "=a"(lo), "=d"(hi) : "c"(0));
+  return ((xxh_u64)hi << 32) | lo;
+}
+#  define XXHRS_LOAD(v) __atomic_load_n(&(v), __ATOMIC_RELAXED)
+#  define XXHRS_STORE(v, x) __atomic_store_n(&(v), (x), __ATOMIC_RELAXED)
+#endif
+
+/* Best kernel supported by both the cpu and the operating system */
+static int XXHRS_detectBackend(void) {
+  unsigned r[4];
+  xxh_u64 xcr0;
+  int best = XXH_SCALAR;
+
+  if (!XXHRS_cpuid(1, 0, r)) return best;
+  if (r[3] & (1u << 26)) best = XXH_SSE2;
+  /* OSXSAVE and AVX; the OS must save the ymm registers */
+  if ((r[2] & (1u << 27)) == 0 || (r[2] & (1u << 28)) == 0) return best;
+  xcr0 = XXHRS_xgetbv();
+  if ((xcr0 & 0x6) != 0x6) return best;
+
+  if (!XXHRS_cpuid(7, 0, r)) return best;
+  if (r[1] & (1u << 5)) best = XXH_AVX2;
+  /* AVX512F; the OS must also save the opmask and zmm registers */
+  if ((r[1] & (1u << 16)) && (xcr0 & 0xe0) == 0xe0) best = XXH_AVX512;
+  return best;
+}
+
+#define XXHRS_KERNELS(name, target) \
+  target static XXH64_hash_t XXHRS_hashLong64_##name(const void* input, size_t len, \
+      XXH64_hash_t seed, const void* secret, size_t secretSize) { \
+    if (secret == NULL) \
+      return XXH3_hashLong_64b_withSeed_internal(input, len, seed, XXH3_accumulate_##name, \
+          XXH3_scrambleAcc_##name, XXH3_initCustomSecret_##name); \
+    return XXH3_hashLong_64b_internal(input, len, secret, secretSize, \
+        XXH3_accumulate_##name, XXH3_scrambleAcc_##name); \
+  } \
+  target static XXH128_hash_t XXHRS_hashLong128_##name(const void* input, size_t len, \
+      XXH64_hash_t seed, const void* secret, size_t secretSize) { \
+    if (secret == NULL) \
+      return XXH3_hashLong_128b_withSeed_internal(input, len, seed, XXH3_accumulate_##name, \
+          XXH3_scrambleAcc_##name, XXH3_initCustomSecret_##name); \
+    return XXH3_hashLong_128b_internal(input, len, (const xxh_u8*)secret, secretSize, \
+        XXH3_accumulate_##name, XXH3_scrambleAcc_##name); \
+  } \
+  target static XXH_errorcode XXHRS_update_##name(XXH3_state_t* state, \
+      const void* input, size_t len) { \
+    return XXH3_update(state, (const xxh_u8*)input, len, \
+        XXH3_accumulate_##name, XXH3_scrambleAcc_##name); \
+  }
+
+XXHRS_KERNELS(scalar, )
+XXHRS_KERNELS(sse2, XXH_TARGET_SSE2)
+XXHRS_KERNELS(avx2, XXH_TARGET_AVX2)
+XXHRS_KERNELS(avx512, XXH_TARGET_AVX512)
+
+typedef struct {
+  XXH64_hash_t (*hashLong64)(const void*, size_t, XXH64_hash_t, const void*, size_t);
+  XXH128_hash_t (*hashLong128)(const void*, size_t, XXH64_hash_t, const void*, size_t);
+  XXH_errorcode (*update)(XXH3_state_t*, const void*, size_t);
+} XXHRS_kernels_t;
+
+/* Indexed by XXH_VECTOR value */
+static const XXHRS_kernels_t XXHRS_kernels[] = {
+  { XXHRS_hashLong64_scalar, XXHRS_hashLong128_scalar, XXHRS_update_scalar },
+  { XXHRS_hashLong64_sse2, XXHRS_hashLong128_sse2, XXHRS_update_sse2 },
+  { XXHRS_hashLong64_avx2, XXHRS_hashLong128_avx2, XXHRS_update_avx2 },
+  { XXHRS_hashLong64_avx512, XXHRS_hashLong128_avx512, XXHRS_update_avx512 },
+};
+
+/* -1 until the first use; detection is idempotent, so racing
+ * initializations are harmless */
+static int XXHRS_g_backend = -1;
+
+int XXHRS_backend(void) {
+  int backend = XXHRS_LOAD(XXHRS_g_backend);
+  if (backend < 0) {
+    backend = XXHRS_detectBackend();
+    XXHRS_STORE(XXHRS_g_backend, backend);
+  }
+  return backend;
+}
+
+XXH_errorcode XXHRS_setBackend(int backend) {
+  if (backend < XXH_SCALAR || backend > XXHRS_detectBackend()) return XXH_ERROR;
+  XXHRS_STORE(XXHRS_g_backend, backend);
+  return XXH_OK;
+}
+
+#define XXHRS_KERNEL(f) (XXHRS_kernels[XXHRS_backend()].f)
+
+#else /* XXHRS_X86DISPATCH */
+
+/* Only the kernel selected at compile time is available */
+
+static XXH64_hash_t XXHRS_hashLong64(const void* input, size_t len,
+    XXH64_hash_t seed, const void* secret, size_t secretSize) {
+  if (secret == NULL)
+    return XXH3_hashLong_64b_withSeed(input, len, seed, NULL, 0);
+  return XXH3_hashLong_64b_withSecret(input, len, 0, (const xxh_u8*)secret, secretSize);
+}
+
+static XXH128_hash_t XXHRS_hashLong128(const void* input, size_t len,
+    XXH64_hash_t seed, const void* secret, size_t secretSize) {
+  if (secret == NULL)
+    return XXH3_hashLong_128b_withSeed(input, len, seed, NULL, 0);
+  return XXH3_hashLong_128b_withSecret(input, len, 0, secret, secretSize);
+}
+
+static XXH_errorcode XXHRS_update(XXH3_state_t* state, const void* input, size_t len) {
+  return XXH3_64bits_update(state, input, len);
+}
+
+int XXHRS_backend(void) {
+  return XXH_VECTOR;
+}
+
+XXH_errorcode XXHRS_setBackend(int backend) {
+  return backend == XXH_VECTOR ? XXH_OK : XXH_ERROR;
+}
+
+#define XXHRS_KERNEL(f) (XXHRS_##f)
+
+#endif /* XXHRS_X86DISPATCH */
+
+/* Short inputs do not use the kernels and are hashed directly */
+
+XXH64_hash_t XXHRS_64bits(const void* input, size_t len) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_64bits(input, len);
+  return XXHRS_KERNEL(hashLong64)(input, len, 0, XXH3_kSecret, sizeof(XXH3_kSecret));
+}
+
+XXH64_hash_t XXHRS_64bits_withSeed(const void* input, size_t len, XXH64_hash_t seed) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_64bits_withSeed(input, len, seed);
+  return XXHRS_KERNEL(hashLong64)(input, len, seed, NULL, 0);
+}
+
+XXH64_hash_t XXHRS_64bits_withSecret(const void* input, size_t len, const void* secret, size_t secretSize) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_64bits_withSecret(input, len, secret, secretSize);
+  return XXHRS_KERNEL(hashLong64)(input, len, 0, secret, secretSize);
+}
+
+XXH64_hash_t XXHRS_64bits_withSecretandSeed(const void* input, size_t len, const void* secret, size_t secretSize, XXH64_hash_t seed) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_64bits_withSecretandSeed(input, len, secret, secretSize, seed);
+  return XXHRS_KERNEL(hashLong64)(input, len, 0, secret, secretSize);
+}
+
+XXH_errorcode XXHRS_64bits_update(XXH3_state_t* statePtr, const void* input, size_t len) {
+  return XXHRS_KERNEL(update)(statePtr, input, len);
+}
+
+XXH128_hash_t XXHRS_128bits(const void* input, size_t len) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_128bits(input, len);
+  return XXHRS_KERNEL(hashLong128)(input, len, 0, XXH3_kSecret, sizeof(XXH3_kSecret));
+}
+
+XXH128_hash_t XXHRS_128bits_withSeed(const void* input, size_t len, XXH64_hash_t seed) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_128bits_withSeed(input, len, seed);
+  return XXHRS_KERNEL(hashLong128)(input, len, seed, NULL, 0);
+}
+
+XXH128_hash_t XXHRS_128bits_withSecret(const void* input, size_t len, const void* secret, size_t secretSize) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_128bits_withSecret(input, len, secret, secretSize);
+  return XXHRS_KERNEL(hashLong128)(input, len, 0, secret, secretSize);
+}
+
+XXH128_hash_t XXHRS_128bits_withSecretandSeed(const void* input, size_t len, const void* secret, size_t secretSize, XXH64_hash_t seed) {
+  if (len <= XXH3_MIDSIZE_MAX) return XXH3_128bits_withSecretandSeed(input, len, secret, secretSize, seed);
+  return XXHRS_KERNEL(hashLong128)(input, len, 0, secret, secretSize);
+}
+
+XXH_errorcode XXHRS_128bits_update(XXH3_state_t* statePtr, const void* input, size_t len) {
+  /* 64 and 128 bit streaming share the same update */
+  return XXHRS_KERNEL(update)(statePtr, input, len);
 }
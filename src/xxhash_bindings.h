@@ -1,14 +1,73 @@
 #pragma once
 #include <stdint.h>
 
+#define XXHRS_NAMESPACE xxhrs_equodaeyiejoopibaeva_
+
+/* The system libxxhash exports the plain XXH* symbols; the build script
+ * maps them to the namespaced names in the rust bindings */
 #define XXH_STATIC_LINKING_ONLY 1
-#define XXH_NAMESPACE xxhrs_equodaeyiejoopibaeva_
+#ifndef XXHRS_SYSTEM_XXHASH
+#  define XXH_NAMESPACE XXHRS_NAMESPACE
+#endif
 #include "xxhash.h"
 
+/* XXH3 output is stable since 0.8.0, but the secret & seed functions need
+ * the behaviour of 0.8.2 */
+#if defined(XXHRS_SYSTEM_XXHASH) && XXH_VERSION_NUMBER < 802
+#  error "xxhrs requires libxxhash 0.8.2 or newer"
+#endif
+
 #define XXHRS_CAT(A,B) A##B
-#define XXHRS_NAME2(A,B) XXH_CAT(A,B)
-#define XXHRS_64bits_reset_withSecretCopy XXHRS_NAME2(XXH_NAMESPACE, XXHRS_64bits_reset_withSecretCopy)
-#define XXHRS_128bits_reset_withSecretCopy XXHRS_NAME2(XXH_NAMESPACE, XXHRS_128bits_reset_withSecretCopy)
+#define XXHRS_NAME2(A,B) XXHRS_CAT(A,B)
+#define XXHRS_64bits_reset_withSecretCopy XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits_reset_withSecretCopy)
+#define XXHRS_128bits_reset_withSecretCopy XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits_reset_withSecretCopy)
+#define XXHRS_64bits_reset_withSecretandSeedCopy XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits_reset_withSecretandSeedCopy)
+#define XXHRS_128bits_reset_withSecretandSeedCopy XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits_reset_withSecretandSeedCopy)
+#define XXHRS_64bits_reset_keepSecret XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits_reset_keepSecret)
+#define XXHRS_128bits_reset_keepSecret XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits_reset_keepSecret)
+#define XXHRS_config XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_config)
+#define XXHRS_backend XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_backend)
+#define XXHRS_setBackend XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_setBackend)
+#define XXHRS_64bits XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits)
+#define XXHRS_64bits_withSeed XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits_withSeed)
+#define XXHRS_64bits_withSecret XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits_withSecret)
+#define XXHRS_64bits_withSecretandSeed XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits_withSecretandSeed)
+#define XXHRS_64bits_update XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_64bits_update)
+#define XXHRS_128bits XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits)
+#define XXHRS_128bits_withSeed XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits_withSeed)
+#define XXHRS_128bits_withSecret XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits_withSecret)
+#define XXHRS_128bits_withSecretandSeed XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits_withSecretandSeed)
+#define XXHRS_128bits_update XXHRS_NAME2(XXHRS_NAMESPACE, XXHRS_128bits_update)
+
+extern void XXHRS_64bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret, size_t secretSize);
+extern void XXHRS_128bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret, size_t secretSize);
+extern void XXHRS_64bits_reset_withSecretandSeedCopy(XXH3_state_t* statePtr, const void* secret, XXH64_hash_t seed);
+extern void XXHRS_128bits_reset_withSecretandSeedCopy(XXH3_state_t* statePtr, const void* secret, XXH64_hash_t seed);
+extern void XXHRS_64bits_reset_keepSecret(XXH3_state_t* statePtr);
+extern void XXHRS_128bits_reset_keepSecret(XXH3_state_t* statePtr);
+
+/* Compile options xxhash was built with */
+typedef struct {
+  int vector;            /* XXH_VECTOR; the default kernel with dispatch */
+  int dispatch;          /* whether the kernel is chosen at runtime */
+  int sizeOpt;           /* XXH_SIZE_OPT */
+  int forceMemoryAccess; /* XXH_FORCE_MEMORY_ACCESS */
+} XXHRS_config_t;
+
+extern XXHRS_config_t XXHRS_config(void);
+
+/* Runtime dispatch between the XXH3 kernels (XXH_VECTOR values) */
+extern int XXHRS_backend(void);
+extern XXH_errorcode XXHRS_setBackend(int backend);
 
-extern void XXHRS_64bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret);
-extern void XXHRS_128bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret);
+/* XXH3 functions using the kernel selected at runtime */
+extern XXH64_hash_t XXHRS_64bits(const void* input, size_t len);
+extern XXH64_hash_t XXHRS_64bits_withSeed(const void* input, size_t len, XXH64_hash_t seed);
+extern XXH64_hash_t XXHRS_64bits_withSecret(const void* input, size_t len, const void* secret, size_t secretSize);
+extern XXH64_hash_t XXHRS_64bits_withSecretandSeed(const void* input, size_t len, const void* secret, size_t secretSize, XXH64_hash_t seed);
+extern XXH_errorcode XXHRS_64bits_update(XXH3_state_t* statePtr, const void* input, size_t len);
+extern XXH128_hash_t XXHRS_128bits(const void* input, size_t len);
+extern XXH128_hash_t XXHRS_128bits_withSeed(const void* input, size_t len, XXH64_hash_t seed);
+extern XXH128_hash_t XXHRS_128bits_withSecret(const void* input, size_t len, const void* secret, size_t secretSize);
+extern XXH128_hash_t XXHRS_128bits_withSecretandSeed(const void* input, size_t len, const void* secret, size_t secretSize, XXH64_hash_t seed);
+extern XXH_errorcode XXHRS_128bits_update(XXH3_state_t* statePtr, const void* input, size_t len);
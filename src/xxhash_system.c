@@ -0,0 +1,138 @@
+/* Our helpers, for use with the system libxxhash.
+ *
+ * Only the public API of the library is available, so the secret copying
+ * helpers go through the public reset functions instead of
+ * XXH3_reset_internal() and patch up the state afterwards. The kernel the
+ * library was compiled with can not be queried or switched. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "xxhash_bindings.h"
+
+#define XXHRS_STRIPE_LEN 64
+
+/* The hashers access the fields of XXH3_state_t, which is not part of the
+ * stable ABI of the library; its layout is taken from the header found at
+ * build time. Refuse to run against any other version of the library, so
+ * a libxxhash upgraded after building can not corrupt the state. */
+#if defined(_MSC_VER) && !defined(__clang__)
+static void XXHRS_checkVersion(void);
+#  pragma section(".CRT$XCU", read)
+__declspec(allocate(".CRT$XCU")) void (*XXHRS_checkVersion_ctor)(void) = XXHRS_checkVersion;
+#  if defined(_M_IX86)
+#    pragma comment(linker, "/include:_XXHRS_checkVersion_ctor")
+#  else
+#    pragma comment(linker, "/include:XXHRS_checkVersion_ctor")
+#  endif
+#else
+static void XXHRS_checkVersion(void) __attribute__((constructor));
+#endif
+
+static void XXHRS_checkVersion(void) {
+  unsigned const version = XXH_versionNumber();
+  if (version != XXH_VERSION_NUMBER) {
+    fprintf(stderr, "xxhrs: built for libxxhash %d, but version %u is loaded\n",
+        XXH_VERSION_NUMBER, version);
+    abort();
+  }
+}
+
+/* secretSize must be between XXH3_SECRET_SIZE_MIN and XXH3_SECRET_DEFAULT_SIZE */
+void XXHRS_64bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret, size_t secretSize)  {
+  XXH3_64bits_reset_withSecret(statePtr, secret, secretSize);
+  memcpy(statePtr->customSecret, secret, secretSize);
+  statePtr->extSecret = NULL;
+}
+
+void XXHRS_128bits_reset_withSecretCopy(XXH3_state_t* statePtr, const void* secret, size_t secretSize)  {
+  XXHRS_64bits_reset_withSecretCopy(statePtr, secret, secretSize);
+}
+
+void XXHRS_64bits_reset_withSecretandSeedCopy(XXH3_state_t* statePtr, const void* secret, XXH64_hash_t seed)  {
+  XXHRS_64bits_reset_withSecretCopy(statePtr, secret, XXH3_SECRET_DEFAULT_SIZE);
+  statePtr->seed = seed;
+  statePtr->useSeed = 1; /* always, even if seed==0 */
+}
+
+void XXHRS_128bits_reset_withSecretandSeedCopy(XXH3_state_t* statePtr, const void* secret, XXH64_hash_t seed)  {
+  XXHRS_64bits_reset_withSecretandSeedCopy(statePtr, secret, seed);
+}
+
+/* The public reset functions reject a NULL secret, which stands for the
+ * copy in customSecret; resetting does not touch customSecret itself */
+void XXHRS_64bits_reset_keepSecret(XXH3_state_t* statePtr) {
+  XXH32_hash_t const useSeed = statePtr->useSeed;
+  const unsigned char* const extSecret = statePtr->extSecret;
+  XXH3_64bits_reset_withSecretandSeed(statePtr,
+      extSecret != NULL ? extSecret : statePtr->customSecret,
+      statePtr->secretLimit + XXHRS_STRIPE_LEN, statePtr->seed);
+  statePtr->extSecret = extSecret;
+  statePtr->useSeed = useSeed;
+}
+
+void XXHRS_128bits_reset_keepSecret(XXH3_state_t* statePtr) {
+  XXHRS_64bits_reset_keepSecret(statePtr);
+}
+
+/* Build configuration; the library's compile options are unknown */
+
+XXHRS_config_t XXHRS_config(void) {
+  XXHRS_config_t r;
+  r.vector = -1;
+  r.dispatch = 0;
+  r.sizeOpt = -1;
+  r.forceMemoryAccess = -1;
+  return r;
+}
+
+/* No runtime dispatch */
+
+int XXHRS_backend(void) {
+  return -1;
+}
+
+XXH_errorcode XXHRS_setBackend(int backend) {
+  (void)backend;
+  return XXH_ERROR;
+}
+
+XXH64_hash_t XXHRS_64bits(const void* input, size_t len) {
+  return XXH3_64bits(input, len);
+}
+
+XXH64_hash_t XXHRS_64bits_withSeed(const void* input, size_t len, XXH64_hash_t seed) {
+  return XXH3_64bits_withSeed(input, len, seed);
+}
+
+XXH64_hash_t XXHRS_64bits_withSecret(const void* input, size_t len, const void* secret, size_t secretSize) {
+  return XXH3_64bits_withSecret(input, len, secret, secretSize);
+}
+
+XXH64_hash_t XXHRS_64bits_withSecretandSeed(const void* input, size_t len, const void* secret, size_t secretSize, XXH64_hash_t seed) {
+  return XXH3_64bits_withSecretandSeed(input, len, secret, secretSize, seed);
+}
+
+XXH_errorcode XXHRS_64bits_update(XXH3_state_t* statePtr, const void* input, size_t len) {
+  return XXH3_64bits_update(statePtr, input, len);
+}
+
+XXH128_hash_t XXHRS_128bits(const void* input, size_t len) {
+  return XXH3_128bits(input, len);
+}
+
+XXH128_hash_t XXHRS_128bits_withSeed(const void* input, size_t len, XXH64_hash_t seed) {
+  return XXH3_128bits_withSeed(input, len, seed);
+}
+
+XXH128_hash_t XXHRS_128bits_withSecret(const void* input, size_t len, const void* secret, size_t secretSize) {
+  return XXH3_128bits_withSecret(input, len, secret, secretSize);
+}
+
+XXH128_hash_t XXHRS_128bits_withSecretandSeed(const void* input, size_t len, const void* secret, size_t secretSize, XXH64_hash_t seed) {
+  return XXH3_128bits_withSecretandSeed(input, len, secret, secretSize, seed);
+}
+
+XXH_errorcode XXHRS_128bits_update(XXH3_state_t* statePtr, const void* input, size_t len) {
+  return XXH3_128bits_update(statePtr, input, len);
+}